@@ -0,0 +1,74 @@
+use binius_field::{BinaryField128b, Field};
+use binius_ntt::SingleThreadedNTT;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use fri_binius::{Code, Encoder, LOG_RATE};
+use rand::thread_rng;
+
+//An Encoder reused across encodes of equal-length messages against a fresh Code::new each time, which allocates the message and codeword anew
+fn encoder_reuse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encoder_reuse");
+    for l in [12, 16, 20] {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+            .unwrap()
+            .multithreaded();
+
+        group.bench_with_input(BenchmarkId::new("code_new", l), &poly, |b, poly| {
+            b.iter(|| Code::new(poly, &ntt))
+        });
+        let mut encoder = Encoder::new();
+        group.bench_with_input(BenchmarkId::new("encoder", l), &poly, |b, poly| {
+            b.iter(|| encoder.encode(poly, &ntt).len())
+        });
+    }
+    group.finish();
+}
+
+//Code::new_and_fold against Code::new followed by fold_code, from messages that fit in cache to ones well past it
+fn new_and_fold(c: &mut Criterion) {
+    let mut group = c.benchmark_group("new_and_fold");
+    for l in [12, 16, 20] {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+            .unwrap()
+            .multithreaded();
+        let r0 = BinaryField128b::random(thread_rng());
+
+        group.bench_with_input(BenchmarkId::new("encode_then_fold", l), &poly, |b, poly| {
+            b.iter(|| Code::new(poly, &ntt).fold_code(r0, 0, &ntt))
+        });
+        group.bench_with_input(BenchmarkId::new("fused", l), &poly, |b, poly| {
+            b.iter(|| Code::new_and_fold(poly, r0, &ntt))
+        });
+    }
+    group.finish();
+}
+
+//Code::new_coset_parallel against the sequential coset loop of Code::new, on the small messages it targets and one large enough for a single
+//transform to occupy the pool
+fn coset_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("coset_parallel");
+    for l in [8, 12, 16] {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+            .unwrap()
+            .multithreaded();
+
+        group.bench_with_input(BenchmarkId::new("sequential", l), &poly, |b, poly| {
+            b.iter(|| Code::new(poly, &ntt))
+        });
+        group.bench_with_input(BenchmarkId::new("coset_parallel", l), &poly, |b, poly| {
+            b.iter(|| Code::new_coset_parallel(poly, &ntt))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encoder_reuse, new_and_fold, coset_parallel);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+use binius_field::{BinaryField32b, BinaryField128b, Field, PackedBinaryField4x128b};
+use binius_ntt::{MultithreadedNTT, SingleThreadedNTT};
+use criterion::{Criterion, criterion_group, criterion_main};
+use fri_binius::{Code, LOG_RATE, TransposedCode, fold, fold_subfield};
+use rand::thread_rng;
+
+const LOG_CODEWORD_LEN: usize = 22;
+
+fn codeword() -> (Code<BinaryField128b>, MultithreadedNTT<BinaryField128b>) {
+    let poly: Vec<BinaryField128b> = (0..1 << (LOG_CODEWORD_LEN - LOG_RATE))
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(LOG_CODEWORD_LEN)
+        .unwrap()
+        .multithreaded();
+    (Code::new(&poly, &ntt), ntt)
+}
+
+//One round 0 fold of a 2^22 codeword: the strided pairs of fold_code against the packed kernels over the transposed halves
+fn fold_layouts(c: &mut Criterion) {
+    let (code, ntt) = codeword();
+    let transposed = TransposedCode::from_code(&code);
+    let r = BinaryField128b::random(thread_rng());
+
+    let mut group = c.benchmark_group("fold");
+    group.sample_size(10);
+
+    group.bench_function("strided", |b| b.iter(|| code.fold_code(r, 0, &ntt)));
+    group.bench_function("transposed_2x128b", |b| {
+        b.iter(|| transposed.fold(r, 0, &ntt))
+    });
+    group.bench_function("transposed_4x128b", |b| {
+        b.iter(|| transposed.fold_packed::<PackedBinaryField4x128b, _, _>(r, 0, &ntt))
+    });
+    group.finish();
+}
+
+//Every pair of a 2^22 codeword folded on one thread with a BinaryField32b challenge, by fold_subfield's subfield multiply and by lifting the
+//challenge to BinaryField128b first
+fn fold_subfield_challenge(c: &mut Criterion) {
+    let (code, ntt) = codeword();
+    let r = BinaryField32b::random(thread_rng());
+    let lifted_r = BinaryField128b::from(r);
+    let pairs = || code.encoding.chunks_exact(2).enumerate();
+
+    let mut group = c.benchmark_group("fold_subfield");
+    group.sample_size(10);
+
+    group.bench_function("lifted", |b| {
+        b.iter(|| {
+            pairs()
+                .map(|(i, pair)| fold(lifted_r, 0, i, pair[0], pair[1], &ntt))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("subfield", |b| {
+        b.iter(|| {
+            pairs()
+                .map(|(i, pair)| fold_subfield(r, 0, i, pair[0], pair[1], &ntt))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+//Two rounds of a 2^22 codeword fused by fold2 against two fold_code calls with the intermediate layer built in between
+fn fold_two_rounds(c: &mut Criterion) {
+    let (code, ntt) = codeword();
+    let (r0, r1) = (
+        BinaryField128b::random(thread_rng()),
+        BinaryField128b::random(thread_rng()),
+    );
+
+    let mut group = c.benchmark_group("fold2");
+    group.sample_size(10);
+
+    group.bench_function("two_folds", |b| {
+        b.iter(|| code.fold_code(r0, 0, &ntt).fold_code(r1, 1, &ntt))
+    });
+    group.bench_function("fused", |b| b.iter(|| code.fold2(r0, r1, 0, &ntt)));
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    fold_layouts,
+    fold_subfield_challenge,
+    fold_two_rounds
+);
+criterion_main!(benches);
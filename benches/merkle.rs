@@ -0,0 +1,53 @@
+use binius_field::{BinaryField128b, Field};
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use fri_binius::{MerkleTree, compute_leaf_hashes_with_arity, merklize_with_threshold};
+use rand::thread_rng;
+use rayon::ThreadPoolBuilder;
+
+const LOG_CODEWORD_LEN: usize = 22;
+
+fn codeword() -> Vec<BinaryField128b> {
+    (0..1 << LOG_CODEWORD_LEN)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect()
+}
+
+//Full builds over the pair leaves of a 2^22 codeword, leaf hashing included, on the global pool and on a single thread
+fn build(c: &mut Criterion) {
+    let vals = codeword();
+    let mut group = c.benchmark_group("merkle_build");
+    group.sample_size(10);
+
+    group.bench_function("parallel", |b| b.iter(|| MerkleTree::build(&vals, 2)));
+    //The pool is built once so that only the build itself is timed
+    let serial_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    group.bench_function("serial", |b| {
+        b.iter(|| serial_pool.install(|| MerkleTree::build(&vals, 2)))
+    });
+    group.finish();
+}
+
+//Internal layers only, from the precomputed leaves of a 2^22 codeword, for picking PARALLEL_LAYER_THRESHOLD. usize::MAX builds every layer serially.
+fn merklize_threshold(c: &mut Criterion) {
+    let leaf_hashes = compute_leaf_hashes_with_arity(&codeword(), 2);
+    let mut group = c.benchmark_group("merklize_threshold");
+    group.sample_size(10);
+
+    for threshold in [1, 1 << 6, 1 << 8, 1 << 10, 1 << 12, 1 << 14, usize::MAX] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threshold),
+            &threshold,
+            |b, &threshold| {
+                b.iter_batched(
+                    || leaf_hashes.clone(),
+                    |leaf_hashes| merklize_with_threshold(leaf_hashes, threshold),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, build, merklize_threshold);
+criterion_main!(benches);
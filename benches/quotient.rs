@@ -0,0 +1,44 @@
+use binius_field::{BinaryField128b, Field};
+use binius_ntt::SingleThreadedNTT;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use fri_binius::{Code, LOG_RATE};
+use rand::thread_rng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+//Code::quotient, which inverts its denominators in batches with Montgomery's trick, against the same quotient with one inversion per symbol
+fn quotient(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quotient");
+    group.sample_size(10);
+    for l in [12, 16, 20] {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE + 1)
+            .unwrap()
+            .multithreaded();
+        let code = Code::new(&poly, &ntt);
+        //The last point of the larger domain is outside the codeword's, so no denominator is zero
+        let z = Code::domain_point((1 << (l + LOG_RATE + 1)) - 1, &ntt);
+        let f_z = BinaryField128b::random(thread_rng());
+
+        group.bench_with_input(BenchmarkId::new("invert_each", l), &code, |b, code| {
+            b.iter(|| {
+                code.encoding
+                    .par_iter()
+                    .enumerate()
+                    .map(|(i, val)| {
+                        let denominator = Code::domain_point(i, &ntt) - z;
+                        (*val - f_z) * denominator.invert().unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batched", l), &code, |b, code| {
+            b.iter(|| code.quotient(z, f_z, &ntt))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, quotient);
+criterion_main!(benches);
@@ -1,4 +1,12 @@
 mod prover;
 mod utils;
 mod verifier;
-mod tests;
\ No newline at end of file
+mod tests;
+#[cfg(test)]
+mod proptests;
+
+//The criterion benches in benches/ are built as a separate crate and only see what is exported here.
+pub use utils::{
+    code::{Code, Encoder, LOG_RATE, TransposedCode, fold, fold_subfield},
+    merkle::{MerkleTree, compute_leaf_hashes_with_arity, merklize_with_threshold},
+};
@@ -0,0 +1,89 @@
+use binius_field::{BinaryField64b, BinaryField128b};
+use binius_ntt::{MultithreadedNTT, SingleThreadedNTT};
+use proptest::prelude::*;
+
+use crate::utils::{
+    code::{Code, LOG_RATE},
+    mle::{LagrangeBases, PackedMLE},
+};
+
+const MAX_VARS: usize = 7;
+
+fn field_elems(len: usize) -> impl Strategy<Value = Vec<BinaryField128b>> {
+    prop::collection::vec(any::<u128>().prop_map(BinaryField128b::new), len)
+}
+
+fn message_and_point() -> impl Strategy<Value = (Vec<BinaryField128b>, Vec<BinaryField128b>)> {
+    (1..MAX_VARS).prop_flat_map(|l| (field_elems(1 << l), field_elems(l)))
+}
+
+fn make_ntt() -> MultithreadedNTT<BinaryField128b> {
+    SingleThreadedNTT::<BinaryField128b>::new(MAX_VARS + LOG_RATE)
+        .unwrap()
+        .multithreaded()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn decode_inverts_encode(
+        message in (1..MAX_VARS).prop_flat_map(|l| {
+            prop::collection::vec(any::<u64>().prop_map(BinaryField64b::new), 1 << l)
+        })
+    ) {
+        let ntt = make_ntt();
+        let code = Code::new(&message, &ntt);
+        let repacked = PackedMLE::new(message, true).repack_for_fri();
+
+        prop_assert_eq!(code.decode(&ntt), repacked.coeffs);
+    }
+
+    #[test]
+    fn linear_combine_is_linear(
+        (messages, coeffs) in (1..MAX_VARS).prop_flat_map(|l| {
+            (prop::collection::vec(field_elems(1 << l), 2), field_elems(2))
+        })
+    ) {
+        let ntt = make_ntt();
+        let codes: Vec<Code<BinaryField128b>> =
+            messages.iter().map(|message| Code::new(message, &ntt)).collect();
+
+        let combined_message: Vec<BinaryField128b> = messages[0]
+            .iter()
+            .zip(messages[1].iter())
+            .map(|(m0, m1)| coeffs[0] * *m0 + coeffs[1] * *m1)
+            .collect();
+
+        prop_assert_eq!(
+            Code::linear_combine(&codes, &coeffs).encoding,
+            Code::new(&combined_message, &ntt).encoding
+        );
+    }
+
+    #[test]
+    fn fold_all_is_multilinear_evaluation((message, point) in message_and_point()) {
+        let ntt = make_ntt();
+        let code = Code::new(&message, &ntt);
+
+        prop_assert_eq!(
+            code.fold_all(&point, &ntt),
+            LagrangeBases::gen_from_point(&point).evaluate(&message)
+        );
+    }
+
+    #[test]
+    fn folding_splits_across_rounds((message, point) in message_and_point()) {
+        let ntt = make_ntt();
+        let code = Code::new(&message, &ntt);
+        let final_value = code.fold_all(&point, &ntt);
+
+        for split in 0..=point.len() {
+            let mut folded_code = code.fold_to_size(&point[..split], code.encoding.len() >> split, &ntt);
+            for round in split..point.len() {
+                folded_code = folded_code.fold_code(point[round], round, &ntt);
+            }
+            prop_assert_eq!(folded_code.idx(0), final_value);
+        }
+    }
+}
@@ -7,24 +7,28 @@ use std::{
 use binius_field::{
     arithmetic_traits::{Square, TaggedSquare}, BinaryField, BinaryField128b, BinaryField1b, ExtensionField, Field, PackedBinaryField128x1b, PackedExtension, PackedField, RepackedExtension, TowerField
 };
-use binius_ntt::{DynamicDispatchNTT, MultithreadedNTT};
+use binius_ntt::{AdditiveNTT, DynamicDispatchNTT, MultithreadedNTT};
 use rand::thread_rng;
 use rayon::{
-    iter::{IntoParallelIterator, ParallelIterator},
+    iter::{
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+        IntoParallelRefMutIterator, ParallelIterator,
+    },
     slice::ParallelSlice,
 };
-use sha3::{Digest, Keccak256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256, digest::generic_array::GenericArray};
 use tracing::instrument;
 
 use crate::{
     utils::{
         TAU,
         channel::{self, Channel},
-        code::{Code, LOG_RATE, RATE},
-        merkle::{Hash, MerkleTree, VectorCommitment, compute_leaf_hashes, merklize},
+        code::{Code, CodeError, CosetOrder, LOG_RATE, RATE},
+        merkle::{Hash, MerkleTree, VectorCommitment, compute_leaf_hashes, compute_leaf_hashes_with_arity, merklize},
         mle::{LagrangeBases, PackedMLE, compute_dot_product, compute_row_batch},
     },
-    verifier::compute_eq_tower_ind,
+    verifier::{VerifyError, compute_eq_tower_ind},
 };
 
 pub struct FriCommitment {
@@ -73,6 +77,61 @@ pub fn commit_oracle(code: &Code<BinaryField128b>) -> (VectorCommitment, MerkleT
     (vector_commitment, merkle_tree)
 }
 
+//commit_oracle fed one coset at a time, e.g. as each coset's transform finishes: the pair leaves of a coset are hashed when it arrives and only the
+//upper layers of the tree are left to finalize. Cosets may arrive in any order and are placed at their slot of order, so the commitment equals
+//commit_oracle of the codeword laid out in that order, Code::new for Natural or Code::new_with_order otherwise.
+#[derive(Clone, Debug)]
+pub struct StreamingCommitter {
+    coset_len: usize,
+    order: CosetOrder,
+    coset_leaves: Vec<Option<Vec<Hash>>>,
+}
+
+impl StreamingCommitter {
+    pub fn new(coset_len: usize, order: CosetOrder) -> StreamingCommitter {
+        assert!(
+            coset_len.is_power_of_two() && coset_len >= 2,
+            "Coset length {coset_len} is not a power of 2 of at least 2"
+        );
+        StreamingCommitter {
+            coset_len,
+            order,
+            coset_leaves: vec![None; RATE],
+        }
+    }
+
+    #[instrument(skip_all, name = "absorb coset", level = "debug")]
+    pub fn absorb_coset(&mut self, coset_idx: usize, coset: &[BinaryField128b]) {
+        assert_eq!(coset.len(), self.coset_len, "Coset {coset_idx} does not have {} symbols", self.coset_len);
+        let slot = self.order.coset_at(coset_idx);
+        assert!(self.coset_leaves[slot].is_none(), "Coset {coset_idx} was already absorbed");
+
+        self.coset_leaves[slot] = Some(compute_leaf_hashes_with_arity(coset, 2));
+    }
+
+    //Builds the tree over the absorbed cosets. Panics if any coset is missing.
+    #[instrument(skip_all, name = "finalize streaming commitment", level = "debug")]
+    pub fn finalize(self) -> (VectorCommitment, MerkleTree) {
+        let mut leaf_hashes = Vec::with_capacity(RATE * self.coset_len / 2);
+        for (slot, leaves) in self.coset_leaves.into_iter().enumerate() {
+            match leaves {
+                Some(leaves) => leaf_hashes.extend(leaves),
+                None => panic!("Coset {} was never absorbed", self.order.coset_at(slot)),
+            }
+        }
+
+        let depth = leaf_hashes.len().trailing_zeros() as usize;
+        let merkle_tree = merklize(leaf_hashes);
+        (
+            VectorCommitment {
+                root: merkle_tree.get_root(),
+                depth,
+            },
+            merkle_tree,
+        )
+    }
+}
+
 ///We assume that each coefficient of mle actually represents a packed vector of F_2 elements equal to number of bits required to represent F or
 ///F's dimension as a vector space over F_2
 
@@ -135,12 +194,12 @@ where
         //Sum check Logic
         let poly = sum_check_round(&repacked_mle, &tensored_eq, sum_check_claim);
 
-        channel.observe_field_elems(&poly.coeffs).expect(&format!(
-            "failed to observe prover oracle in sum check: round {round}"
+        channel.observe_field_elems(&poly.coeffs).unwrap_or_else(|e| panic!(
+            "failed to observe prover oracle in sum check: round {round}: {e:?}"
         ));
 
-        let r = channel.get_random_point().expect(&format!(
-            "failed to get verifier challenge in sumcheck: round {round}"
+        let r = channel.get_random_point().unwrap_or_else(|e| panic!(
+            "failed to get verifier challenge in sumcheck: round {round}: {e:?}"
         ));
 
         sum_check_claim = poly.evaluate(r);
@@ -255,6 +314,493 @@ impl EvalProof {
     }
 }
 
+//Openings of one FRI query through every committed layer, starting with the committed codeword: the queried pair and its Merkle path per layer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryPath {
+    #[serde(with = "serde_symbol_pairs")]
+    pub symbols: Vec<(BinaryField128b, BinaryField128b)>,
+    pub merkle_paths: Vec<Vec<Hash>>,
+}
+
+//Everything a verifier needs to check that a committed codeword is close to the code: the roots of the committed folded layers, one opening per
+//query and the coefficients of the final layer, which is sent in the clear instead of being committed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriProof {
+    pub layer_roots: Vec<Hash>,
+    pub query_openings: Vec<QueryPath>,
+    #[serde(with = "serde_symbols")]
+    pub final_poly: Vec<BinaryField128b>,
+}
+
+//BinaryField128b has no serde support, so proof symbols go through serde as their u128 value in the tower basis.
+pub(crate) mod serde_symbols {
+    use binius_field::BinaryField128b;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(symbols: &[BinaryField128b], serializer: S) -> Result<S::Ok, S::Error> {
+        symbols.iter().map(|symbol| symbol.val()).collect::<Vec<u128>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BinaryField128b>, D::Error> {
+        Ok(Vec::<u128>::deserialize(deserializer)?.into_iter().map(BinaryField128b::new).collect())
+    }
+}
+
+mod serde_symbol_pairs {
+    use binius_field::BinaryField128b;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        pairs: &[(BinaryField128b, BinaryField128b)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pairs.iter().map(|(v0, v1)| (v0.val(), v1.val())).collect::<Vec<(u128, u128)>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(BinaryField128b, BinaryField128b)>, D::Error> {
+        Ok(Vec::<(u128, u128)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(v0, v1)| (BinaryField128b::new(v0), BinaryField128b::new(v1)))
+            .collect())
+    }
+}
+
+impl FriProof {
+    //Compact wire format of the proof, separate from its serde encoding: little endian u64 lengths followed by the raw hashes and the 16 byte little
+    //endian symbols. It does not depend on a serde format, which is what lets fri_proof_size predict its length exactly.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        write_len(&mut bytes, self.layer_roots.len());
+        for root in self.layer_roots.iter() {
+            bytes.extend_from_slice(&root.0);
+        }
+
+        write_len(&mut bytes, self.final_poly.len());
+        for coeff in self.final_poly.iter() {
+            bytes.extend_from_slice(&coeff.val().to_le_bytes());
+        }
+
+        write_len(&mut bytes, self.query_openings.len());
+        for opening in self.query_openings.iter() {
+            write_len(&mut bytes, opening.symbols.len());
+            for (symbols, merkle_path) in opening.symbols.iter().zip(opening.merkle_paths.iter()) {
+                bytes.extend_from_slice(&symbols.0.val().to_le_bytes());
+                bytes.extend_from_slice(&symbols.1.val().to_le_bytes());
+
+                write_len(&mut bytes, merkle_path.len());
+                for hash in merkle_path.iter() {
+                    bytes.extend_from_slice(&hash.0);
+                }
+            }
+        }
+        bytes
+    }
+
+    //Inverse of to_bytes. Truncated input or trailing bytes are rejected as a malformed proof.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<FriProof, VerifyError> {
+        let bytes = &mut bytes;
+
+        let layer_roots = (0..read_len(bytes)?)
+            .map(|_| read_hash(bytes))
+            .collect::<Result<Vec<Hash>, VerifyError>>()?;
+
+        let final_poly = (0..read_len(bytes)?)
+            .map(|_| read_symbol(bytes))
+            .collect::<Result<Vec<BinaryField128b>, VerifyError>>()?;
+
+        let query_openings = (0..read_len(bytes)?)
+            .map(|_| {
+                let layers = read_len(bytes)?;
+                let mut symbols = Vec::new();
+                let mut merkle_paths = Vec::new();
+                for _ in 0..layers {
+                    symbols.push((read_symbol(bytes)?, read_symbol(bytes)?));
+                    merkle_paths.push(
+                        (0..read_len(bytes)?)
+                            .map(|_| read_hash(bytes))
+                            .collect::<Result<Vec<Hash>, VerifyError>>()?,
+                    );
+                }
+                Ok(QueryPath {
+                    symbols,
+                    merkle_paths,
+                })
+            })
+            .collect::<Result<Vec<QueryPath>, VerifyError>>()?;
+
+        if !bytes.is_empty() {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        Ok(FriProof {
+            layer_roots,
+            query_openings,
+            final_poly,
+        })
+    }
+}
+
+//Predicted length of FriProof::to_bytes for a message of 1 << message_dim coefficients encoded at rate 1 / (1 << log_rate), folded fold_arity symbols at a
+//time until 1 << final_dim coefficients remain. Each of the num_queries queries opens fold_arity symbols and a Merkle path in every layer but the last,
+//and each committed layer adds a digest_bytes root. The u64 length prefixes of the serialization are counted too, so the prediction is exact for the
+//arity 2 proofs of prove_fri.
+pub fn fri_proof_size(
+    message_dim: usize,
+    log_rate: usize,
+    num_queries: usize,
+    fold_arity: usize,
+    final_dim: usize,
+    digest_bytes: usize
+) -> usize {
+    assert!(
+        fold_arity.is_power_of_two() && fold_arity >= 2,
+        "Fold arity {fold_arity} is not a power of 2 of at least 2"
+    );
+    let log_arity = fold_arity.trailing_zeros() as usize;
+    assert!(
+        final_dim < message_dim && (message_dim - final_dim) % log_arity == 0,
+        "Cannot fold from dimension {message_dim} to {final_dim} with arity {fold_arity}"
+    );
+
+    let (len_bytes, symbol_bytes) = (8, 16);
+    let log_len = message_dim + log_rate;
+    let rounds = (message_dim - final_dim) / log_arity;
+
+    let query_bytes: usize = len_bytes +
+        (0..rounds)
+            .map(|round| {
+                let path_len = log_len - (round + 1) * log_arity;
+                fold_arity * symbol_bytes + len_bytes + path_len * digest_bytes
+            })
+            .sum::<usize>();
+
+    len_bytes + (rounds - 1) * digest_bytes +
+        len_bytes + (1 << final_dim) * symbol_bytes +
+        len_bytes + num_queries * query_bytes
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], VerifyError> {
+    if bytes.len() < len {
+        return Err(VerifyError::MalformedProof);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn read_len(bytes: &mut &[u8]) -> Result<usize, VerifyError> {
+    Ok(u64::from_le_bytes(read_bytes(bytes, 8)?.try_into().unwrap()) as usize)
+}
+
+fn read_hash(bytes: &mut &[u8]) -> Result<Hash, VerifyError> {
+    Ok(Hash(GenericArray::clone_from_slice(read_bytes(bytes, 32)?)))
+}
+
+fn read_symbol(bytes: &mut &[u8]) -> Result<BinaryField128b, VerifyError> {
+    Ok(BinaryField128b::new(u128::from_le_bytes(
+        read_bytes(bytes, 16)?.try_into().unwrap(),
+    )))
+}
+
+//FRI proximity proof for a codeword committed with commit_oracle. The codeword is folded until it has target_len symbols, committing every layer
+//but the last, whose coefficients are sent instead. Queries are opened through every committed layer, as in prove.
+#[instrument(skip_all, name = "prove_fri", level = "debug")]
+pub fn prove_fri<P, N>(
+    code: &Code<BinaryField128b>,
+    commitment: &VectorCommitment,
+    merkle_tree: &MerkleTree,
+    target_len: usize,
+    ntt: &N,
+    channel: &mut Channel,
+) -> FriProof
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    let fold_tree = FoldTree::commit(code, commitment, merkle_tree, target_len, ntt, channel);
+
+    let queries = channel
+        .gen_queries(code.encoding.len().trailing_zeros() as usize)
+        .expect("Failed to generate FRI queries.");
+
+    fold_tree.extract_proof(&queries)
+}
+
+//The commit phase of prove_fri: the committed codeword, every folded layer and the Merkle trees of the committed ones, with the final polynomial.
+//The folded layers are owned, the committed codeword and its tree are borrowed from the caller of commit_oracle.
+pub struct FoldTree<'a> {
+    code: &'a Code<BinaryField128b>,
+    merkle_tree: &'a MerkleTree,
+    layers: Vec<Code<BinaryField128b>>,
+    layer_roots: Vec<Hash>,
+    layer_merkle_trees: Vec<MerkleTree>,
+    final_poly: Vec<BinaryField128b>,
+}
+
+impl<'a> FoldTree<'a> {
+    //Folds the codeword until it has target_len symbols, drawing each challenge from the channel and observing each committed root and the final
+    //polynomial, so the channel is left where prove_fri draws its queries.
+    #[instrument(skip_all, name = "fold tree commit", level = "debug")]
+    pub fn commit<P, N>(
+        code: &'a Code<BinaryField128b>,
+        commitment: &VectorCommitment,
+        merkle_tree: &'a MerkleTree,
+        target_len: usize,
+        ntt: &N,
+        channel: &mut Channel,
+    ) -> FoldTree<'a>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            target_len.is_power_of_two() && target_len >= RATE && target_len < code.encoding.len(),
+            "Target length {target_len} must be a power of 2 between {RATE} and {}",
+            code.encoding.len() / 2
+        );
+        let rounds = (code.encoding.len() / target_len).trailing_zeros() as usize;
+
+        channel.observe_vector_commitment(commitment);
+
+        let mut layers: Vec<Code<BinaryField128b>> = Vec::with_capacity(rounds);
+        let mut layer_roots = Vec::with_capacity(rounds - 1);
+        let mut layer_merkle_trees: Vec<MerkleTree> = Vec::with_capacity(rounds - 1);
+
+        for round in 0..rounds {
+            let r = channel.get_random_point().unwrap_or_else(|e| panic!(
+                "failed to get folding challenge: round {round}: {e:?}"
+            ));
+
+            let folded_code = match round {
+                0 => code.fold_code(r, round, ntt),
+                _ => layers[round - 1].fold_code(r, round, ntt),
+            };
+
+            if round < rounds - 1 {
+                let (layer_commitment, layer_merkle_tree) = commit_oracle(&folded_code);
+                channel.observe_vector_commitment(&layer_commitment);
+                layer_roots.push(layer_commitment.root);
+                layer_merkle_trees.push(layer_merkle_tree);
+            }
+            layers.push(folded_code);
+        }
+
+        let final_poly = layers[rounds - 1].decode_at_round(rounds, ntt);
+        channel
+            .observe_field_elems(&final_poly)
+            .expect("failed to observe final polynomial");
+
+        FoldTree {
+            code,
+            merkle_tree,
+            layers,
+            layer_roots,
+            layer_merkle_trees,
+            final_poly,
+        }
+    }
+
+    //Proof opening only the given queries, indices into the committed codeword: per query the pair holding it in every committed layer, walking
+    //down by halving the index, and the Merkle path of that pair. The layers and trees are not copied into the proof.
+    pub fn extract_proof(&self, query_indices: &[usize]) -> FriProof {
+        let rounds = self.layers.len();
+        let query_openings = query_indices
+            .iter()
+            .map(|query| {
+                assert!(
+                    *query < self.code.encoding.len(),
+                    "Query {query} out of range for a codeword of length {}",
+                    self.code.encoding.len()
+                );
+                let mut leaf_index = query >> 1;
+                let mut symbols = Vec::with_capacity(rounds);
+                let mut merkle_paths = Vec::with_capacity(rounds);
+
+                for round in 0..rounds {
+                    let (layer, layer_merkle_tree) = match round {
+                        0 => (self.code, self.merkle_tree),
+                        _ => (&self.layers[round - 1], &self.layer_merkle_trees[round - 1]),
+                    };
+                    symbols.push((layer.idx(leaf_index << 1), layer.idx((leaf_index << 1) | 1)));
+                    merkle_paths.push(layer_merkle_tree.get_merkle_path(leaf_index));
+                    leaf_index >>= 1;
+                }
+
+                QueryPath {
+                    symbols,
+                    merkle_paths,
+                }
+            })
+            .collect();
+
+        FriProof {
+            layer_roots: self.layer_roots.clone(),
+            query_openings,
+            final_poly: self.final_poly.clone(),
+        }
+    }
+}
+
+//prove_fri that refuses the zero codeword with CodeError::ZeroMessage. The zero codeword passes FRI trivially, so for protocols where it means the
+//message failed to populate this turns a vacuous proof into an error.
+pub fn try_prove_fri<P, N>(
+    code: &Code<BinaryField128b>,
+    commitment: &VectorCommitment,
+    merkle_tree: &MerkleTree,
+    target_len: usize,
+    ntt: &N,
+    channel: &mut Channel,
+) -> Result<FriProof, CodeError>
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    if code.is_zero() {
+        return Err(CodeError::ZeroMessage);
+    }
+    Ok(prove_fri(code, commitment, merkle_tree, target_len, ntt, channel))
+}
+
+//Builds the combined codeword of batch FRI one polynomial at a time: add commits to a codeword, accumulates coeff times it into the running combination
+//and drops it, so at most one standalone codeword is held next to the combination instead of all of them.
+#[derive(Clone, Debug, Default)]
+pub struct BatchFriBuilder {
+    combined: Option<Code<BinaryField128b>>,
+    commitments: Vec<VectorCommitment>,
+}
+
+impl BatchFriBuilder {
+    pub fn new() -> BatchFriBuilder {
+        BatchFriBuilder::default()
+    }
+
+    #[instrument(skip_all, name = "batch fri add", level = "debug")]
+    pub fn add(&mut self, code: Code<BinaryField128b>, coeff: BinaryField128b) {
+        let (commitment, _) = commit_oracle(&code);
+        self.commitments.push(commitment);
+
+        match self.combined.as_mut() {
+            Some(combined) => {
+                assert_eq!(
+                    combined.encoding.len(),
+                    code.encoding.len(),
+                    "Codewords of a batch must have equal length"
+                );
+                combined
+                    .encoding
+                    .par_iter_mut()
+                    .zip(code.encoding.par_iter())
+                    .for_each(|(val, code_val)| *val += coeff * *code_val);
+            }
+            None => {
+                let encoding = code.encoding.par_iter().map(|val| coeff * *val).collect();
+                self.combined = Some(Code { encoding });
+            }
+        }
+    }
+
+    //The combined codeword and the commitments of the added codewords in the order they were added.
+    pub fn finish(self) -> (Code<BinaryField128b>, Vec<VectorCommitment>) {
+        (
+            self.combined.expect("No codewords were added to the batch"),
+            self.commitments,
+        )
+    }
+}
+
+//States of a FriTranscriptProver: Uncommitted holds a layer not yet observed into the transcript, Committed holds the challenge sampled after it was.
+#[derive(Clone, Copy, Debug)]
+pub struct Uncommitted;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Committed {
+    r: BinaryField128b,
+}
+
+//FRI commit phase with the transcript order in the types: commit observes the current layer's root and samples the challenge, and fold, which only
+//exists once committed, folds with exactly that challenge. Folding before observing, or with a challenge from elsewhere, does not compile.
+pub struct FriTranscriptProver<'a, S> {
+    layer: Code<BinaryField128b>,
+    round: usize,
+    commitments: Vec<VectorCommitment>,
+    merkle_trees: Vec<MerkleTree>,
+    channel: &'a mut Channel,
+    state: S,
+}
+
+impl<'a> FriTranscriptProver<'a, Uncommitted> {
+    pub fn new(code: Code<BinaryField128b>, channel: &'a mut Channel) -> FriTranscriptProver<'a, Uncommitted> {
+        FriTranscriptProver {
+            layer: code,
+            round: 0,
+            commitments: Vec::new(),
+            merkle_trees: Vec::new(),
+            channel,
+            state: Uncommitted,
+        }
+    }
+
+    //Commits to the current layer, observes the commitment and returns the folding challenge sampled after it.
+    pub fn commit(self) -> (FriTranscriptProver<'a, Committed>, BinaryField128b) {
+        let FriTranscriptProver { layer, round, mut commitments, mut merkle_trees, channel, .. } = self;
+
+        let (commitment, merkle_tree) = commit_oracle(&layer);
+        channel.observe_vector_commitment(&commitment);
+        commitments.push(commitment);
+        merkle_trees.push(merkle_tree);
+
+        let r = channel.get_random_point().unwrap_or_else(|e| panic!(
+            "failed to get folding challenge: round {round}: {e:?}"
+        ));
+
+        (
+            FriTranscriptProver {
+                layer,
+                round,
+                commitments,
+                merkle_trees,
+                channel,
+                state: Committed { r },
+            },
+            r,
+        )
+    }
+
+    //The current, uncommitted layer and the commitments and trees of the layers before it, in round order.
+    pub fn finish(self) -> (Code<BinaryField128b>, Vec<VectorCommitment>, Vec<MerkleTree>) {
+        (self.layer, self.commitments, self.merkle_trees)
+    }
+}
+
+impl<'a> FriTranscriptProver<'a, Committed> {
+    //Folds the committed layer with the challenge commit returned.
+    pub fn fold<P, N>(self, ntt: &N) -> FriTranscriptProver<'a, Uncommitted>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        FriTranscriptProver {
+            layer: self.layer.fold_code(self.state.r, self.round, ntt),
+            round: self.round + 1,
+            commitments: self.commitments,
+            merkle_trees: self.merkle_trees,
+            channel: self.channel,
+            state: Uncommitted,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Univariate {
     pub coeffs: Vec<BinaryField128b>,
@@ -2,15 +2,43 @@ use std::time::Instant;
 
 use binius_field::{ BinaryField128b, BinaryField32b, BinaryField64b, Field };
 use binius_ntt::{ MultithreadedNTT, SingleThreadedNTT };
-use rand::thread_rng;
+use rand::{ rngs::StdRng, thread_rng, SeedableRng };
 use rayon::iter::{ IntoParallelIterator, ParallelIterator };
 use tracing_profile::init_tracing;
 
 use crate::{
-    prover::{ commit, prove },
-    utils::{ channel::Channel, code::LOG_RATE, mle::{ self, LagrangeBases, PackedMLE } },
-    verifier::verify,
+    prover::{
+        commit,
+        commit_oracle,
+        fri_proof_size,
+        prove,
+        prove_fri,
+        try_prove_fri,
+        BatchFriBuilder,
+        FoldTree,
+        FriProof,
+        FriTranscriptProver,
+        StreamingCommitter,
+    },
+    utils::{ channel::Channel, code::{ Code, CodeError, CosetOrder, FoldSchedule, LOG_RATE, RATE }, merkle::VectorCommitment, mle::{ self, multilinear_eval, LagrangeBases, PackedMLE } },
+    verifier::{
+        compute_eq_table,
+        verify,
+        verify_batch_combination,
+        verify_final_degree,
+        verify_fold_step,
+        verify_fold_step_ct,
+        verify_fold_step_arity,
+        verify_fold_with_eq,
+        verify_fri_sumcheck_link,
+        FriParams,
+        FriVerifier,
+        QueryVerifier,
+        VerifyError,
+    },
 };
+#[cfg(test)]
+use crate::utils::code::random_poly_and_ntt;
 
 #[test]
 fn fri_test() {
@@ -72,3 +100,601 @@ fn fri_test() {
         println!("Time: {:?} \n", time.elapsed());
     }
 }
+
+#[test]
+fn fri_proof_test() {
+    let l = 10;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+
+    let target_len = 1 << 5;
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, target_len, &ntt, &mut Channel::new());
+    assert_eq!(fri_proof.layer_roots.len(), l + LOG_RATE - 5 - 1);
+    assert_eq!(fri_proof.final_poly.len(), target_len >> LOG_RATE);
+
+    assert_eq!(fri_proof.verify(&commitment, &mut Channel::new(), &ntt), Ok(()));
+
+    let decoded_proof = FriProof::from_bytes(&fri_proof.to_bytes()).unwrap();
+    assert_eq!(decoded_proof, fri_proof);
+    assert_eq!(decoded_proof.verify(&commitment, &mut Channel::new(), &ntt), Ok(()));
+    assert_eq!(FriProof::from_bytes(&fri_proof.to_bytes()[1..]), Err(VerifyError::MalformedProof));
+
+    let json = serde_json::to_string(&fri_proof).unwrap();
+    assert_eq!(serde_json::from_str::<FriProof>(&json).unwrap(), fri_proof);
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.query_openings[3].symbols[2].0 += BinaryField128b::ONE;
+    assert_eq!(
+        bad_proof.verify(&commitment, &mut Channel::new(), &ntt),
+        Err(VerifyError::MerklePath { round: 2, query: 3 })
+    );
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.final_poly[0] += BinaryField128b::ONE;
+    assert!(bad_proof.verify(&commitment, &mut Channel::new(), &ntt).is_err());
+}
+
+#[test]
+fn fold_tree_extract_proof_test() {
+    let l = 10;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+
+    let target_len = 1 << 5;
+    let rounds = l + LOG_RATE - 5;
+    let mut channel = Channel::new();
+    let fold_tree = FoldTree::commit(&code, &commitment, &merkle_tree, target_len, &ntt, &mut channel);
+    let queries = channel.gen_queries(l + LOG_RATE).unwrap();
+
+    let fri_proof = fold_tree.extract_proof(&queries);
+    assert_eq!(fri_proof.query_openings.len(), queries.len());
+    assert!(
+        fri_proof.query_openings
+            .iter()
+            .all(|opening| opening.symbols.len() == rounds && opening.merkle_paths.len() == rounds)
+    );
+    assert_eq!(fri_proof, prove_fri(&code, &commitment, &merkle_tree, target_len, &ntt, &mut Channel::new()));
+    assert_eq!(fri_proof.verify(&commitment, &mut Channel::new(), &ntt), Ok(()));
+
+    //Openings depend only on their own query
+    let partial_proof = fold_tree.extract_proof(&queries[..3]);
+    assert_eq!(partial_proof.query_openings.len(), 3);
+    assert_eq!(partial_proof.query_openings[..], fri_proof.query_openings[..3]);
+}
+
+#[test]
+fn sample_query_indices_test() {
+    //144 queries of a 256 symbol codeword are all but certain to collide
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+
+    let mut channel = Channel::new();
+    let fold_tree = FoldTree::commit(&code, &commitment, &merkle_tree, RATE, &ntt, &mut channel);
+    let queries = channel.sample_query_indices(l + LOG_RATE, false).unwrap();
+
+    //Replaying the commit phase leaves a second channel in the same state
+    let mut channel = Channel::new();
+    FoldTree::commit(&code, &commitment, &merkle_tree, RATE, &ntt, &mut channel);
+    let deduped = channel.sample_query_indices(l + LOG_RATE, true).unwrap();
+    assert_eq!(queries.len(), 144);
+    assert!(deduped.len() < queries.len());
+    assert!(deduped.iter().enumerate().all(|(i, query)| !deduped[..i].contains(query)));
+    assert!(queries.iter().all(|query| deduped.contains(query)));
+
+    for dedupe in [false, true] {
+        let fri_proof = fold_tree.extract_proof(if dedupe { &deduped } else { &queries });
+
+        let mut query_verifier = QueryVerifier::from_transcript_sampled(
+            &commitment,
+            &fri_proof.layer_roots,
+            &fri_proof.final_poly,
+            &mut Channel::new(),
+            dedupe,
+            &ntt
+        ).unwrap();
+        for opening in fri_proof.query_openings.iter() {
+            assert_eq!(query_verifier.feed(opening), Ok(()));
+        }
+        assert_eq!(query_verifier.finish(), Ok(()));
+
+        //A verifier sampling under the other policy expects a different number of openings
+        let mut query_verifier = QueryVerifier::from_transcript_sampled(
+            &commitment,
+            &fri_proof.layer_roots,
+            &fri_proof.final_poly,
+            &mut Channel::new(),
+            !dedupe,
+            &ntt
+        ).unwrap();
+        let result = fri_proof.query_openings
+            .iter()
+            .try_for_each(|opening| query_verifier.feed(opening))
+            .and_then(|_| query_verifier.finish());
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn fri_verifier_test() {
+    let l = 10;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, 1 << 5, &ntt, &mut Channel::new());
+
+    let params = FriParams { message_dim: l, log_rate: LOG_RATE, num_queries: 144, final_dim: 3 };
+    let fri_verifier = FriVerifier::new(params, &ntt);
+    assert_eq!(fri_verifier.verify(&commitment.root, &fri_proof, &mut Channel::new()), Ok(()));
+
+    //A prover whose transcript diverged folds with challenges the verifier does not draw. The proof is consistent with those challenges but not with the
+    //verifier's.
+    let mut channel = Channel::new();
+    channel.observe_field_elem(BinaryField128b::ONE).unwrap();
+    let substituted_proof = prove_fri(&code, &commitment, &merkle_tree, 1 << 5, &ntt, &mut channel);
+    assert!(fri_verifier.verify(&commitment.root, &substituted_proof, &mut Channel::new()).is_err());
+
+    let short_params = FriParams { final_dim: 4, ..params };
+    assert_eq!(
+        FriVerifier::new(short_params, &ntt).verify(&commitment.root, &fri_proof, &mut Channel::new()),
+        Err(VerifyError::MalformedProof)
+    );
+}
+
+#[test]
+fn validate_shape_test() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, 1 << 4, &ntt, &mut Channel::new());
+
+    let params = FriParams { message_dim: l, log_rate: LOG_RATE, num_queries: 144, final_dim: 2 };
+    assert_eq!(params.num_rounds(), 6);
+    assert_eq!(fri_proof.validate_shape(&params), Ok(()));
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.layer_roots.pop();
+    assert_eq!(bad_proof.validate_shape(&params), Err(VerifyError::MalformedProof));
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.final_poly.push(BinaryField128b::ZERO);
+    assert_eq!(bad_proof.validate_shape(&params), Err(VerifyError::MalformedProof));
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.query_openings.pop();
+    assert_eq!(bad_proof.validate_shape(&params), Err(VerifyError::QueryCount { expected: 144, received: 143 }));
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.query_openings[5].symbols.pop();
+    assert_eq!(bad_proof.validate_shape(&params), Err(VerifyError::MalformedProof));
+
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.query_openings[5].merkle_paths[2].pop();
+    assert_eq!(bad_proof.validate_shape(&params), Err(VerifyError::BadProofLength { got: 6, expected: 7 }));
+
+    //Shape alone says nothing about the symbols
+    let mut bad_proof = fri_proof.clone();
+    bad_proof.query_openings[5].symbols[2].0 += BinaryField128b::ONE;
+    assert_eq!(bad_proof.validate_shape(&params), Ok(()));
+
+    let past_message = FriParams { final_dim: l + 1, ..params };
+    assert_eq!(fri_proof.validate_shape(&past_message), Err(VerifyError::MalformedProof));
+    let overflowing = FriParams { log_rate: usize::MAX, ..params };
+    assert_eq!(fri_proof.validate_shape(&overflowing), Err(VerifyError::MalformedProof));
+}
+
+#[test]
+fn query_verifier_test() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, 1 << 4, &ntt, &mut Channel::new());
+
+    let mut query_verifier = QueryVerifier::from_transcript(
+        &commitment,
+        &fri_proof.layer_roots,
+        &fri_proof.final_poly,
+        &mut Channel::new(),
+        &ntt
+    ).unwrap();
+
+    let bad_query = 5;
+    for (query, opening) in fri_proof.query_openings.iter().enumerate() {
+        if query == bad_query {
+            let mut bad_opening = opening.clone();
+            bad_opening.symbols[1] = bad_opening.symbols[0];
+            assert!(query_verifier.feed(&bad_opening).is_err());
+        }
+        assert_eq!(query_verifier.feed(opening), Ok(()));
+    }
+    assert_eq!(query_verifier.finish(), Ok(()));
+
+    let mut query_verifier = QueryVerifier::from_transcript(
+        &commitment,
+        &fri_proof.layer_roots,
+        &fri_proof.final_poly,
+        &mut Channel::new(),
+        &ntt
+    ).unwrap();
+    query_verifier.feed(&fri_proof.query_openings[0]).unwrap();
+    assert_eq!(
+        query_verifier.finish(),
+        Err(VerifyError::QueryCount { expected: fri_proof.query_openings.len(), received: 1 })
+    );
+}
+
+#[test]
+fn verify_fold_with_eq_test() {
+    let l = 6;
+    let eq_point: Vec<BinaryField128b> = (0..l).map(|_| BinaryField128b::random(thread_rng())).collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap().multithreaded();
+
+    let code = Code::new(&compute_eq_table(&eq_point), &ntt);
+    let r = BinaryField128b::random(thread_rng());
+    let folded_code = code.fold_code(r, 0, &ntt);
+
+    //A codeword that is not the eq polynomial's but is folded honestly
+    let poly: Vec<BinaryField128b> = (0..1 << l).map(|_| BinaryField128b::random(thread_rng())).collect();
+    let other_code = Code::new(&poly, &ntt);
+    let other_folded_code = other_code.fold_code(r, 0, &ntt);
+
+    for idx in [0, 7, 100, code.encoding.len() - 1] {
+        let (left, right) = (code.idx(idx & !1), code.idx(idx | 1));
+        let expected = folded_code.idx(idx >> 1);
+        assert!(verify_fold_with_eq(0, idx, left, right, r, expected, &eq_point, &ntt));
+
+        //The eq value holds but the next layer opens a different symbol
+        assert!(!verify_fold_with_eq(0, idx, left, right, r, expected + BinaryField128b::ONE, &eq_point, &ntt));
+
+        //The fold relation holds but the folded symbol is not the eq polynomial's value
+        let (other_left, other_right) = (other_code.idx(idx & !1), other_code.idx(idx | 1));
+        let other_expected = other_folded_code.idx(idx >> 1);
+        assert!(!verify_fold_with_eq(0, idx, other_left, other_right, r, other_expected, &eq_point, &ntt));
+    }
+
+    //Later rounds read the twiddles of round + 1 for the eq layer
+    let r1 = BinaryField128b::random(thread_rng());
+    let twice_folded_code = folded_code.fold_code(r1, 1, &ntt);
+    let partial_point: Vec<BinaryField128b> = eq_point[1..].to_vec();
+    //Dividing out eq(eq_point[0], r) leaves the eq codeword of the remaining variables
+    let unscale = (BinaryField128b::ONE + eq_point[0] + r).invert().unwrap();
+    for idx in [0, 5, folded_code.encoding.len() - 1] {
+        let (left, right) = (folded_code.idx(idx & !1) * unscale, folded_code.idx(idx | 1) * unscale);
+        let expected = twice_folded_code.idx(idx >> 1) * unscale;
+        assert!(verify_fold_with_eq(1, idx, left, right, r1, expected, &partial_point, &ntt));
+    }
+}
+
+#[test]
+fn verify_fold_step_test() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let schedule = FoldSchedule::new(&ntt, l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in 0..l {
+        let r = BinaryField128b::random(thread_rng());
+        let folded_code = code.fold_code_cached(r, round, &schedule);
+
+        for idx in [0, 5, code.encoding.len() - 1] {
+            let (left, right) = (code.idx(idx & !1), code.idx(idx | 1));
+            let expected = folded_code.idx(idx >> 1);
+
+            assert!(verify_fold_step(&schedule, round, idx, left, right, r, expected));
+            assert!(!verify_fold_step(&schedule, round, idx, left, right, r, expected + BinaryField128b::ONE));
+        }
+        code = folded_code;
+    }
+}
+
+#[test]
+fn verify_final_degree_test() {
+    let log_degree = 3;
+    let mut final_coeffs: Vec<BinaryField128b> = (0..1 << log_degree)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    final_coeffs.resize(1 << (log_degree + 2), BinaryField128b::ZERO);
+
+    assert!(verify_final_degree(&final_coeffs, log_degree));
+    assert!(verify_final_degree(&final_coeffs[..1 << log_degree], log_degree));
+
+    final_coeffs[1 << log_degree] = BinaryField128b::ONE;
+    assert!(!verify_final_degree(&final_coeffs, log_degree));
+    assert!(verify_final_degree(&final_coeffs, log_degree + 1));
+}
+
+#[test]
+fn verify_fold_step_ct_test() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let schedule = FoldSchedule::new(&ntt, l);
+
+    let code = Code::new(&poly, &ntt);
+    let r = BinaryField128b::random(thread_rng());
+    let folded_code = code.fold_code_cached(r, 0, &schedule);
+
+    for idx in 0..code.encoding.len() {
+        let (left, right) = (code.idx(idx & !1), code.idx(idx | 1));
+        for expected in [folded_code.idx(idx >> 1), folded_code.idx(idx >> 1) + BinaryField128b::ONE] {
+            assert_eq!(
+                bool::from(verify_fold_step_ct(&schedule, 0, idx, left, right, r, expected)),
+                verify_fold_step(&schedule, 0, idx, left, right, r, expected)
+            );
+        }
+    }
+}
+
+#[test]
+fn batch_fri_builder_test() {
+    let l = 6;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap().multithreaded();
+
+    let codes: Vec<Code<BinaryField128b>> = (0..5)
+        .map(|_| {
+            let poly: Vec<BinaryField128b> = (0..1 << l).map(|_| BinaryField128b::random(thread_rng())).collect();
+            Code::new(&poly, &ntt)
+        })
+        .collect();
+    let coeffs: Vec<BinaryField128b> = (0..5).map(|_| BinaryField128b::random(thread_rng())).collect();
+
+    let mut builder = BatchFriBuilder::new();
+    for (code, coeff) in codes.iter().zip(coeffs.iter()) {
+        builder.add(code.clone(), *coeff);
+    }
+    let (combined, commitments) = builder.finish();
+
+    assert_eq!(combined, Code::linear_combine(&codes, &coeffs));
+    for (code, commitment) in codes.iter().zip(commitments.iter()) {
+        assert_eq!(*commitment, commit_oracle(code).0);
+    }
+}
+
+#[test]
+fn fri_proof_size_test() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+
+    for log_target_len in [LOG_RATE, 4, 6] {
+        let fri_proof = prove_fri(&code, &commitment, &merkle_tree, 1 << log_target_len, &ntt, &mut Channel::new());
+        let predicted = fri_proof_size(
+            l,
+            LOG_RATE,
+            fri_proof.query_openings.len(),
+            2,
+            log_target_len - LOG_RATE,
+            32
+        );
+        assert_eq!(predicted, fri_proof.to_bytes().len());
+    }
+}
+
+#[test]
+fn fri_transcript_prover_test() {
+    let (l, rounds) = (6, 4);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let run = |code: &Code<BinaryField128b>| {
+        let mut channel = Channel::new();
+        let mut prover = FriTranscriptProver::new(code.clone(), &mut channel);
+        let mut challenges = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            let (committed, r) = prover.commit();
+            challenges.push(r);
+            prover = committed.fold(&ntt);
+        }
+        let (last_layer, commitments, _) = prover.finish();
+        (challenges, last_layer, commitments)
+    };
+
+    let code = Code::new(&poly, &ntt);
+    let (challenges, last_layer, commitments) = run(&code);
+    assert_eq!(commitments.len(), rounds);
+    assert_eq!(commitments[0], commit_oracle(&code).0);
+    assert_eq!(last_layer.encoding.len(), code.encoding.len() >> rounds);
+
+    //Same transcript as prove_fri folding the same code
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, code.encoding.len() >> (rounds + 1), &ntt, &mut Channel::new());
+    for (layer_root, layer_commitment) in fri_proof.layer_roots.iter().zip(commitments[1..].iter()) {
+        assert_eq!(*layer_root, layer_commitment.root);
+    }
+
+    //Changing the committed data changes every challenge sampled after it
+    let mut tampered = code.clone();
+    tampered.encoding[3] += BinaryField128b::ONE;
+    let (tampered_challenges, _, _) = run(&tampered);
+    for (r, tampered_r) in challenges.iter().zip(tampered_challenges.iter()) {
+        assert_ne!(r, tampered_r);
+    }
+}
+
+#[test]
+fn verify_fri_sumcheck_link_test() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let z: Vec<BinaryField128b> = (0..l).map(|_| BinaryField128b::random(thread_rng())).collect();
+    let r: Vec<BinaryField128b> = (0..l).map(|_| BinaryField128b::random(thread_rng())).collect();
+
+    //Final claim of a sumcheck of poly(x) * eq(z, x) ending at r
+    let eq_correction: BinaryField128b = z
+        .iter()
+        .zip(r.iter())
+        .map(|(z_i, r_i)| BinaryField128b::ONE + *z_i + *r_i)
+        .product();
+    assert_eq!(eq_correction, LagrangeBases::gen_from_point(&r).evaluate(&compute_eq_table(&z)));
+    let sumcheck_claim = multilinear_eval(&poly, &r) * eq_correction;
+
+    let fri_final = Code::new(&poly, &ntt).fold_all(&r, &ntt);
+    assert!(verify_fri_sumcheck_link(fri_final, sumcheck_claim, eq_correction));
+
+    assert!(!verify_fri_sumcheck_link(fri_final + BinaryField128b::ONE, sumcheck_claim, eq_correction));
+    assert!(!verify_fri_sumcheck_link(fri_final, sumcheck_claim, eq_correction + BinaryField128b::ONE));
+
+    //FRI folded with the challenges in the wrong order
+    let reversed: Vec<BinaryField128b> = r.iter().rev().copied().collect();
+    let fri_final = Code::new(&poly, &ntt).fold_all(&reversed, &ntt);
+    assert!(!verify_fri_sumcheck_link(fri_final, sumcheck_claim, eq_correction));
+}
+
+#[test]
+fn try_prove_fri_test() {
+    let l = 6;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap().multithreaded();
+
+    let code = Code::new(&vec![BinaryField128b::ZERO; 1 << l], &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    assert!(matches!(
+        try_prove_fri(&code, &commitment, &merkle_tree, 1 << 4, &ntt, &mut Channel::new()),
+        Err(CodeError::ZeroMessage)
+    ));
+
+    let poly: Vec<BinaryField128b> = (0..1 << l).map(|_| BinaryField128b::random(thread_rng())).collect();
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+    let fri_proof = try_prove_fri(&code, &commitment, &merkle_tree, 1 << 4, &ntt, &mut Channel::new()).unwrap();
+    assert_eq!(fri_proof, prove_fri(&code, &commitment, &merkle_tree, 1 << 4, &ntt, &mut Channel::new()));
+}
+
+#[test]
+fn verify_batch_combination_test() {
+    let l = 5;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap().multithreaded();
+
+    let codes: Vec<Code<BinaryField128b>> = (0..4)
+        .map(|_| {
+            let poly: Vec<BinaryField128b> = (0..1 << l).map(|_| BinaryField128b::random(thread_rng())).collect();
+            Code::new(&poly, &ntt)
+        })
+        .collect();
+    let coeffs: Vec<BinaryField128b> = (0..4).map(|_| BinaryField128b::random(thread_rng())).collect();
+    let combined = Code::linear_combine(&codes, &coeffs);
+
+    for idx in [0, 9, combined.encoding.len() - 1] {
+        let mut openings: Vec<(usize, BinaryField128b)> = codes.iter().map(|code| (idx, code.idx(idx))).collect();
+        assert!(verify_batch_combination(&openings, &coeffs, combined.idx(idx)));
+        assert!(!verify_batch_combination(&openings[1..], &coeffs, combined.idx(idx)));
+
+        openings[2].1 += BinaryField128b::ONE;
+        assert!(!verify_batch_combination(&openings, &coeffs, combined.idx(idx)));
+
+        //Correct values but one opened at another position
+        openings[2] = (idx ^ 1, codes[2].idx(idx));
+        assert!(!verify_batch_combination(&openings, &coeffs, combined.idx(idx)));
+    }
+}
+
+#[test]
+fn streaming_committer_test() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    for order in [CosetOrder::Natural, CosetOrder::BitReversed] {
+        let code = Code::new_with_order(&poly, order, &ntt);
+
+        let mut committer = StreamingCommitter::new(1 << l, order);
+        //Out of order, as cosets finishing on different threads would arrive
+        for c in (0..RATE).rev() {
+            committer.absorb_coset(c, code.coset(c, order));
+        }
+        let (commitment, merkle_tree) = committer.finalize();
+
+        assert_eq!((commitment, merkle_tree), commit_oracle(&code));
+    }
+}
+
+#[test]
+fn verify_fold_step_arity_test() {
+    let (l, arity) = (6, 4);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    //Arity fold from round 1 so the twiddles are not those of round 0
+    let code = Code::new(&poly, &ntt).fold_code(BinaryField128b::random(thread_rng()), 0, &ntt);
+    let challenges: Vec<BinaryField128b> = (0..2).map(|_| BinaryField128b::random(thread_rng())).collect();
+    let folded_code = code.fold_rounds(&challenges, 1, &ntt);
+
+    for parent_idx in [0, 3, folded_code.encoding.len() - 1] {
+        let siblings = &code.encoding[parent_idx * arity..(parent_idx + 1) * arity];
+        let parent_value = folded_code.idx(parent_idx);
+        assert!(verify_fold_step_arity(1, parent_idx, siblings, &challenges, parent_value, arity, &ntt));
+
+        let mut corrupted = siblings.to_vec();
+        corrupted[2] += BinaryField128b::ONE;
+        assert!(!verify_fold_step_arity(1, parent_idx, &corrupted, &challenges, parent_value, arity, &ntt));
+        assert!(!verify_fold_step_arity(1, parent_idx, siblings, &challenges[..1], parent_value, arity, &ntt));
+    }
+}
+
+//Seeded prover half of roundtrip_from_seed: the message is drawn from StdRng seeded with seed and the transcript starts by observing the seed, so the
+//whole proof is a function of (seed, message_dim).
+#[cfg(test)]
+fn prove_from_seed(
+    seed: u64,
+    message_dim: usize
+) -> (VectorCommitment, FriProof, MultithreadedNTT<BinaryField128b>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let poly: Vec<BinaryField128b> = (0..1 << message_dim).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(message_dim + LOG_RATE).unwrap().multithreaded();
+
+    let code = Code::new(&poly, &ntt);
+    let (commitment, merkle_tree) = commit_oracle(&code);
+
+    let mut channel = seeded_channel(seed);
+    let fri_proof = prove_fri(&code, &commitment, &merkle_tree, RATE, &ntt, &mut channel);
+    (commitment, fri_proof, ntt)
+}
+
+#[cfg(test)]
+fn seeded_channel(seed: u64) -> Channel {
+    let mut channel = Channel::new();
+    channel.observe_field_elem(BinaryField64b::new(seed)).expect("failed to observe seed");
+    channel
+}
+
+//Deterministic prove, serialize, deserialize and verify cycle of FRI for a seeded random message of 2^message_dim symbols. The rate is fixed at compile
+//time and the query count by the channel, so log_rate must be LOG_RATE and num_queries must match the queries the channel samples, otherwise the
+//function panics and errors with VerifyError::QueryCount respectively.
+#[cfg(test)]
+fn roundtrip_from_seed(seed: u64, message_dim: usize, log_rate: usize, num_queries: usize) -> Result<(), VerifyError> {
+    assert_eq!(log_rate, LOG_RATE, "The rate is fixed at 2^-{LOG_RATE}");
+
+    let (commitment, fri_proof, ntt) = prove_from_seed(seed, message_dim);
+    if fri_proof.query_openings.len() != num_queries {
+        return Err(VerifyError::QueryCount { expected: num_queries, received: fri_proof.query_openings.len() });
+    }
+
+    let decoded_proof = FriProof::from_bytes(&fri_proof.to_bytes())?;
+    decoded_proof.verify(&commitment, &mut seeded_channel(seed), &ntt)
+}
+
+#[test]
+fn roundtrip_from_seed_test() {
+    let message_dim = 8;
+    for seed in [0, 1, 0xdead_beef] {
+        assert_eq!(roundtrip_from_seed(seed, message_dim, LOG_RATE, 144), Ok(()));
+    }
+    assert_eq!(prove_from_seed(7, message_dim).1, prove_from_seed(7, message_dim).1);
+    assert!(roundtrip_from_seed(1, message_dim, LOG_RATE, 10).is_err());
+
+    //A flipped byte of the first layer root changes every later challenge
+    let (commitment, fri_proof, ntt) = prove_from_seed(1, message_dim);
+    let mut bytes = fri_proof.to_bytes();
+    bytes[8] ^= 1;
+    let tampered_proof = FriProof::from_bytes(&bytes).unwrap();
+    assert!(tampered_proof.verify(&commitment, &mut seeded_channel(1), &ntt).is_err());
+}
@@ -1,4 +1,4 @@
-use std::io::BufWriter;
+use std::{collections::HashSet, io::BufWriter};
 
 use binius_field::{BinaryField, BinaryField128b, TowerField, error, serialize_canonical};
 use binius_utils::serialization::{self, DeserializeBytes, Error, SerializeBytes};
@@ -6,7 +6,7 @@ use sha3::{Digest, Keccak256};
 
 use crate::prover::FriCommitment;
 
-use super::merkle::VectorCommitment;
+use super::{code::Code, merkle::VectorCommitment};
 
 //Instantiation of Fiat-Shamir transform
 pub struct Channel {
@@ -61,6 +61,18 @@ impl Channel {
         self.state.update(commitment.packing_factor.to_le_bytes())
     }
 
+    //Observes a codeword prefixed by a domain separation label, so the same codeword observed in two protocol contexts yields different challenges.
+    //The label is length prefixed to keep label and symbols unambiguous. Prover and verifier must observe with the same label.
+    pub fn observe_code(
+        &mut self,
+        label: &'static str,
+        code: &Code<BinaryField128b>,
+    ) -> Result<(), Error> {
+        self.state.update(label.len().to_le_bytes());
+        self.state.update(label.as_bytes());
+        self.observe_field_elems(&code.encoding)
+    }
+
     pub fn get_random_point(&mut self) -> Result<BinaryField128b, Error> {
         self.state.update(self.round_idx.to_le_bytes());
         let mut out = self.state.clone().finalize();
@@ -83,7 +95,7 @@ impl Channel {
         }
     }
 
-    //Number of queries hard coded for 96 bits of security and R=4 as per [DP24]
+    //Number of queries hard coded for 96 bits of security and R=4 as per [DP24]. Positions may repeat, see sample_query_indices.
     pub fn gen_queries(
         &mut self,
         log_max_len: usize,
@@ -106,4 +118,90 @@ impl Channel {
         }
        
     }
+
+    //gen_queries, optionally without repeated positions. With dedupe a position drawn again is dropped, keeping the first draw of each in order, so the
+    //effective number of queries can be lower than gen_queries'. A repeat opens the same symbols and adds no soundness. Without dedupe a repeat is opened
+    //again and checked again. Prover and verifier must sample with the same flag, as it sets the number of openings the verifier expects.
+    pub fn sample_query_indices(&mut self, log_max_len: usize, dedupe: bool) -> Result<Vec<usize>, Error> {
+        let mut queries = self.gen_queries(log_max_len)?;
+        if dedupe {
+            let mut seen = HashSet::new();
+            queries.retain(|query| seen.insert(*query));
+        }
+        Ok(queries)
+    }
+}
+
+//Number of queries needed for target_bits of security with inverse rate rate. In the unique decoding regime a query of a word that is far from the code
+//passes with probability at most 1 - δ = (1 + ρ) / 2 for ρ = 1 / rate [DP24], so the queries needed are target_bits / -log2((1 + ρ) / 2),
+//rounded up. For 96 bits at the crate's RATE = 4 this gives 142, which the 144 queries of gen_queries cover.
+pub fn soundness_queries(rate: usize, target_bits: f64) -> usize {
+    assert!(rate > 1, "Inverse rate {rate} must be larger than 1");
+    assert!(target_bits > 0.0, "Target soundness must be positive");
+
+    let pass_probability = (1.0 + 1.0 / rate as f64) / 2.0;
+    (target_bits / -pass_probability.log2()).ceil() as usize
+}
+
+//Soundness of FRI in bits, -log2 of the total error for inverse rate rate, num_queries queries and num_rounds folds of fold_arity symbols each, assuming
+//the unique decoding proximity gap of [DP24] (after [BCIKS20]): a word far from the code folds to a word close to it with probability at most
+//(fold_arity - 1) * n / |F| per round, n = rate * fold_arity^num_rounds being the length of a codeword folded down to a single coefficient and
+//|F| = 2^128. With the query term of soundness_queries the error is
+//  num_rounds * (fold_arity - 1) * n / 2^128 + ((1 + 1 / rate) / 2)^num_queries.
+//Below about 100 bits the commit phase term is negligible and this inverts soundness_queries up to rounding. It also caps the soundness at about
+//128 - log2(num_rounds * (fold_arity - 1) * n) bits, so no query count reaches 128 bits over BinaryField128b.
+pub fn fri_soundness_bits(rate: usize, num_queries: usize, fold_arity: usize, num_rounds: usize) -> f64 {
+    assert!(rate > 1, "Inverse rate {rate} must be larger than 1");
+    assert!(fold_arity >= 2, "Fold arity {fold_arity} must be at least 2");
+
+    let log_len = (rate as f64).log2() + num_rounds as f64 * (fold_arity as f64).log2();
+    let commit_error = (num_rounds * (fold_arity - 1)) as f64 * (log_len - 128.0).exp2();
+    let query_error = ((1.0 + 1.0 / rate as f64) / 2.0).powi(num_queries as i32);
+    -(commit_error + query_error).log2()
+}
+
+#[test]
+fn test_soundness_queries() {
+    assert_eq!(soundness_queries(4, 96.0), 142);
+    assert_eq!(soundness_queries(4, 128.0), 189);
+    assert_eq!(soundness_queries(2, 100.0), 241);
+    assert_eq!(soundness_queries(16, 128.0), 141);
+}
+
+#[test]
+fn test_fri_soundness_bits() {
+    for (rate, target_bits) in [(4, 96.0), (2, 100.0), (16, 80.0)] {
+        let num_queries = soundness_queries(rate, target_bits);
+        assert!(fri_soundness_bits(rate, num_queries, 2, 10) >= target_bits);
+        assert!(fri_soundness_bits(rate, num_queries - 1, 2, 10) < target_bits);
+    }
+
+    //The commit phase term caps the soundness below 128 - log2(num_rounds * n) bits however many queries are made
+    assert!(fri_soundness_bits(4, soundness_queries(4, 128.0), 2, 20) < 128.0 - 22.0);
+
+    //Folds of 2^128 / n symbols leave no soundness, whatever the queries
+    assert!(fri_soundness_bits(4, 1000, 2, 126) < 1.0);
+}
+
+#[test]
+fn test_observe_code_label() {
+    let code = Code {
+        encoding: (0..64).map(|i| BinaryField128b::new(i as u128)).collect(),
+    };
+
+    let mut challenges = ["commit", "query"].into_iter().map(|label| {
+        let mut channel = Channel::new();
+        channel.observe_code(label, &code).unwrap();
+        channel.get_random_point().unwrap()
+    });
+    assert_ne!(challenges.next(), challenges.next());
+
+    let mut channel = Channel::new();
+    channel.observe_code("commit", &code).unwrap();
+    let mut other_channel = Channel::new();
+    other_channel.observe_code("commit", &code).unwrap();
+    assert_eq!(
+        channel.get_random_point().unwrap(),
+        other_channel.get_random_point().unwrap()
+    );
 }
@@ -1,46 +1,610 @@
-use std::error;
+use std::{
+    error,
+    fmt::{self, Display, Formatter},
+    iter,
+    ops::Range,
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use binius_field::{
     BinaryField, BinaryField1b, BinaryField32b, BinaryField64b, BinaryField128b, ExtensionField,
-    Field, PackedExtension, RepackedExtension, TowerField, as_packed_field::PackScalar,
+    Field, PackedBinaryField2x128b, PackedBinaryField4x128b, PackedExtension, PackedField,
+    RepackedExtension, TowerField, as_packed_field::PackScalar,
 };
-use binius_ntt::{AdditiveNTT, Error, MultithreadedNTT, SingleThreadedNTT};
-use rand::thread_rng;
+use binius_ntt::{AdditiveNTT, DynamicDispatchNTT, Error, MultithreadedNTT, SingleThreadedNTT};
+use rand::{Rng, thread_rng};
 use rayon::{
+    Yield,
     iter::{
         IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
-        IntoParallelRefMutIterator, ParallelIterator,
+        IntoParallelRefMutIterator, ParallelExtend, ParallelIterator,
     },
-    slice::ParallelSlice,
+    slice::{ParallelSlice, ParallelSliceMut},
 };
-use tracing::instrument;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
 
-use crate::utils::mle::LagrangeBases;
+use crate::utils::{
+    merkle::{CodeHeader, Hash, MerkleTree, SymbolHasher, bind_header},
+    mle::LagrangeBases,
+};
 
 pub const RATE: usize = 4;
 pub const LOG_RATE: usize = 2;
+
+//Errors returned by the fallible Code constructors.
+#[derive(Debug)]
+pub enum CodeError {
+    Io(std::io::Error),
+    Ntt(Error),
+    OffsetOutOfRange { offset: usize, max_offset: usize },
+    CosetMismatch { coset: usize, base_idx: usize },
+    UnalignedMessage { message_len: usize, degree: usize },
+    FieldMismatch { log_domain: usize, field_bits: usize },
+    UnexpectedLength { expected: usize, actual: usize },
+    ZeroChallenge { round: usize },
+    IndexOverflow { index: usize, scale: usize },
+    InconsistentFold { round: usize, idx: usize },
+    NotInSubfield { idx: usize },
+    ZeroMessage,
+    ChallengeCountMismatch { got: usize, expected: usize },
+    OutputLength { expected: usize, actual: usize },
+    MessageTooLong { message_symbols: usize, max_symbols: usize },
+    TooFewSymbols { got: usize, needed: usize },
+    PositionOutOfRange { position: usize, len: usize },
+    DimensionOverflow { message_dim: usize },
+}
+
+impl Display for CodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::Io(error) => write!(f, "I/O error: {error}"),
+            CodeError::Ntt(error) => write!(f, "NTT error: {error}"),
+            CodeError::OffsetOutOfRange { offset, max_offset } => write!(
+                f,
+                "Coset offset {offset} out of range, valid offsets are 0..={max_offset}"
+            ),
+            CodeError::CosetMismatch { coset, base_idx } => {
+                write!(
+                    f,
+                    "Not a codeword, coset {coset} first disagrees with coset 0 at base index {base_idx}"
+                )
+            }
+            CodeError::UnalignedMessage {
+                message_len,
+                degree,
+            } => write!(
+                f,
+                "Message of {message_len} elements does not pack into BinaryField128b symbols of {degree} elements"
+            ),
+            CodeError::FieldMismatch {
+                log_domain,
+                field_bits,
+            } => write!(
+                f,
+                "Codeword needs a domain of 2^{log_domain} points, an NTT over a field of {field_bits} bits has at most 2^{field_bits}"
+            ),
+            CodeError::UnexpectedLength { expected, actual } => {
+                write!(f, "Expected {expected} bytes, got {actual}")
+            }
+            CodeError::ZeroChallenge { round } => {
+                write!(f, "Folding challenge of round {round} is zero")
+            }
+            CodeError::IndexOverflow { index, scale } => {
+                write!(f, "Index {index} times {scale} overflows usize")
+            }
+            CodeError::NotInSubfield { idx } => {
+                write!(f, "Symbol at index {idx} is not in BinaryField1b")
+            }
+            CodeError::ChallengeCountMismatch { got, expected } => write!(
+                f,
+                "Got {got} folding challenges, folding this codeword down to {RATE} symbols takes {expected}"
+            ),
+            CodeError::OutputLength { expected, actual } => write!(
+                f,
+                "Output buffer holds {actual} symbols, the fold writes {expected}"
+            ),
+            CodeError::TooFewSymbols { got, needed } => write!(
+                f,
+                "{got} distinct symbols are known, interpolating the message takes {needed}"
+            ),
+            CodeError::PositionOutOfRange { position, len } => write!(
+                f,
+                "Position {position} out of range for a codeword of length {len}"
+            ),
+            CodeError::DimensionOverflow { message_dim } => write!(
+                f,
+                "A codeword of 2^{message_dim} symbols at rate 1/{RATE} does not fit in usize"
+            ),
+            CodeError::MessageTooLong {
+                message_symbols,
+                max_symbols,
+            } => write!(
+                f,
+                "Message of {message_symbols} symbols is longer than the {max_symbols} the NTT domain encodes at rate 1/{RATE}"
+            ),
+            CodeError::ZeroMessage => {
+                write!(f, "Codeword is all zero, the message was never populated")
+            }
+            CodeError::InconsistentFold { round, idx } => {
+                write!(
+                    f,
+                    "Layer of round {round} does not fold to the next layer at index {idx}"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for CodeError {}
+
+impl From<std::io::Error> for CodeError {
+    fn from(error: std::io::Error) -> Self {
+        CodeError::Io(error)
+    }
+}
+
+impl From<Error> for CodeError {
+    fn from(error: Error) -> Self {
+        CodeError::Ntt(error)
+    }
+}
+
+//Order in which the RATE cosets are laid out in a codeword, for interop with verifiers using bit-reversed commitment layouts. Folding, decoding and
+//every other method on Code assume Natural.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CosetOrder {
+    #[default]
+    Natural,
+    BitReversed,
+}
+
+impl CosetOrder {
+    //Coset stored in slot of the layout. Both orders are involutions, so this also maps a coset to its slot.
+    pub fn coset_at(self, slot: usize) -> usize {
+        assert!(slot < RATE, "Slot {slot} out of range for {RATE} cosets");
+        match self {
+            CosetOrder::Natural => slot,
+            CosetOrder::BitReversed => slot.reverse_bits() >> (usize::BITS as usize - LOG_RATE),
+        }
+    }
+
+    //Position in the layout of the symbol at natural codeword position idx.
+    pub fn position(self, idx: usize, coset_len: usize) -> usize {
+        self.coset_at(idx / coset_len) * coset_len + idx % coset_len
+    }
+}
+
+//Message fields F that new_ext can encode with an NTT over P, bundling the bounds of try_new_ext between F, P and BinaryField128b under one name so generic
+//callers write F: EncodableWith<P>. Bounds in a trait's where clause are not implied where the trait is used, so the encode itself is a trait method
+//and the bounds are only stated once, on the blanket impl.
+pub trait EncodableWith<P: BinaryField>: BinaryField + TowerField {
+    fn try_encode_ext<N>(message: &[Self], ntt: &N) -> Result<Code<BinaryField128b>, CodeError>
+    where
+        N: AdditiveNTT<P>;
+}
+
+impl<F, P> EncodableWith<P> for F
+where
+    BinaryField128b: ExtensionField<F> + ExtensionField<P> + PackedExtension<P>,
+    F: BinaryField + TowerField + ExtensionField<P>,
+    P: BinaryField,
+{
+    fn try_encode_ext<N>(message: &[F], ntt: &N) -> Result<Code<BinaryField128b>, CodeError>
+    where
+        N: AdditiveNTT<P>,
+    {
+        Code::try_new_ext(message, ntt)
+    }
+}
+
 //Struct containing the Reed-Solomon encoding of a message of packed elements. We assume the elements of the message contain packed base field elements.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Code<F: BinaryField> {
     pub encoding: Vec<F>,
 }
 
 impl Code<BinaryField128b> {
+    //message is read as the coefficients of a polynomial in the novel basis of the NTT [LCH14], not the monomial basis: symbol idx of the codeword is
+    //sum_j message[j] * X_j at the domain point of idx, see novel_basis_eval. Packed subfield messages are repacked into BinaryField128b coefficients first.
     #[instrument(skip_all, name = "encode", level = "debug")]
-    pub fn new<F>(message: &[F], ntt: &MultithreadedNTT<BinaryField128b>) -> Code<BinaryField128b>
+    pub fn new<F, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+    {
+        Self::try_new(message, ntt).expect("failed to encode message")
+    }
+
+    //Fallible new. A message whose codeword would not fit in the NTT domain is reported as MessageTooLong before it is repacked, instead of surfacing
+    //as an NTT error from the first transform.
+    #[instrument(skip_all, name = "try_encode", level = "debug")]
+    pub fn try_new<F, N>(message: &[F], ntt: &N) -> Result<Code<BinaryField128b>, CodeError>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+    {
+        let message_symbols = message
+            .len()
+            .div_ceil(<BinaryField128b as ExtensionField<F>>::DEGREE);
+        let max_symbols = (1 << ntt.log_domain_size()) / RATE;
+        if message_symbols > max_symbols {
+            return Err(CodeError::MessageTooLong {
+                message_symbols,
+                max_symbols,
+            });
+        }
+
+        let repacked_message = repack(message);
+        if cfg!(debug_assertions)
+            && repacked_message.len() >= SINGLE_THREADED_WARN_LEN
+            && !ntt.is_multithreaded()
+        {
+            warn!(
+                "Encoding {} symbols with a single-threaded NTT, call .multithreaded() on it",
+                repacked_message.len() * RATE
+            );
+        }
+        Ok(Self::encode_repacked(&repacked_message, ntt))
+    }
+
+    //Encodes a message produced lazily, collecting it straight into the repacked buffer. The exact size lets the buffer be allocated once.
+    #[instrument(skip_all, name = "encode_from_iter", level = "debug")]
+    pub fn new_from_iter<I, N>(message: I, ntt: &N) -> Code<BinaryField128b>
+    where
+        I: ExactSizeIterator<Item = BinaryField128b>,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let mut repacked_message = Vec::with_capacity(message.len());
+        repacked_message.extend(message);
+        Self::encode_repacked(&repacked_message, ntt)
+    }
+
+    //Code::new with the RATE coset transforms run as separate rayon tasks instead of one after another, for messages too small for a single transform
+    //to occupy the pool. Coset i is the i-th coset_len block of the codeword exactly as in Code::new, and par_chunks_mut hands each task the &mut of its
+    //own block, so the writes are disjoint by construction and the buffer is filled in place without unsafe or a copy per coset.
+    #[instrument(skip_all, name = "encode_coset_parallel", level = "debug")]
+    pub fn new_coset_parallel<F, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b> + Sync,
+    {
+        let repacked_message = repack(message);
+        let coset_len = repacked_message.len();
+
+        let mut encoding = vec![BinaryField128b::ZERO; coset_len * RATE];
+        encoding
+            .par_chunks_mut(coset_len)
+            .enumerate()
+            .for_each(|(i, coset)| {
+                coset.copy_from_slice(&repacked_message);
+                ntt.forward_transform(coset, i as u32, 0).unwrap();
+            });
+        Code { encoding }
+    }
+
+    //Code::new run on a dedicated thread, for callers that cannot block on the encode. The message and NTT are shared rather than borrowed since the
+    //thread outlives the call. The thread is not a rayon worker, so the encode never waits for a worker slot, and its parallel work fans out over the
+    //global pool as a call from outside any pool would.
+    pub fn new_async(
+        message: Arc<Vec<BinaryField128b>>,
+        ntt: Arc<MultithreadedNTT<BinaryField128b>>,
+    ) -> EncodeHandle {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            //The handle may have been dropped, in which case there is nobody to hand the codeword to
+            let _ = sender.send(Code::new(message.as_slice(), ntt.as_ref()));
+        });
+        EncodeHandle { receiver }
+    }
+
+    //Encodes message on the RATE cosets starting at coset index offset instead of 0, so several codewords of one message can live on disjoint cosets.
+    //For a message of 2^m symbols the NTT has 2^(log_domain_size - m) cosets, so offset + RATE must not exceed that count.
+    #[instrument(skip_all, name = "encode_with_offset", level = "debug")]
+    pub fn new_with_offset<F, N>(
+        message: &[F],
+        offset: usize,
+        ntt: &N,
+    ) -> Result<Code<BinaryField128b>, CodeError>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+        let log_len = repacked_message.len().trailing_zeros() as usize;
+        let cosets = ntt
+            .log_domain_size()
+            .checked_sub(log_len)
+            .map_or(0, |log_cosets| 1 << log_cosets);
+
+        if offset.checked_add(RATE).is_none_or(|end| end > cosets) {
+            return Err(CodeError::OffsetOutOfRange {
+                offset,
+                max_offset: cosets.saturating_sub(RATE),
+            });
+        }
+
+        let mut encoding = Vec::with_capacity(repacked_message.len() * RATE);
+        let mut temp;
+
+        for i in offset..offset + RATE {
+            temp = repacked_message.clone();
+            ntt.forward_transform(&mut temp, i as u32, 0)?;
+            encoding.append(&mut temp);
+        }
+        Ok(Code { encoding })
+    }
+
+    //Encodes with the NTT run over packed elements of P, WIDTH symbols at a time. Packed scalars are laid out consecutively, so the codeword is the same
+    //as Code::new and only the butterfly throughput changes. Messages shorter than WIDTH fall back to the scalar kernel.
+    #[instrument(skip_all, name = "encode_packed", level = "debug")]
+    pub fn new_packed<F, P, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
     where
         BinaryField128b: ExtensionField<F>,
         F: BinaryField + TowerField,
+        P: PackedField<Scalar = BinaryField128b>,
+        N: AdditiveNTT<BinaryField128b>,
     {
-        let repacked_message: Vec<BinaryField128b> = message
-            .par_chunks(<BinaryField128b as ExtensionField<F>>::DEGREE)
-            .map(|base_elems| BinaryField128b::from_bases(base_elems).unwrap())
+        let repacked_message = repack(message);
+        if repacked_message.len() < P::WIDTH {
+            return Self::encode_repacked(&repacked_message, ntt);
+        }
+
+        let packed_message: Vec<P> = repacked_message
+            .chunks(P::WIDTH)
+            .map(|scalars| P::from_scalars(scalars.iter().copied()))
             .collect();
+
         let mut encoding = Vec::with_capacity(repacked_message.len() * RATE);
         let mut temp;
 
         for i in 0..RATE as u32 {
+            temp = packed_message.clone();
+            ntt.forward_transform(&mut temp, i, 0).unwrap();
+            encoding.extend(temp.iter().flat_map(|packed| packed.iter()));
+        }
+        Code { encoding }
+    }
+
+    //Code::new of the scalar stream of a packed subfield buffer, e.g. PackedBinaryField128x1b, read without unpacking it first. Scalar i of the stream is
+    //packed[i / WIDTH].get(i % WIDTH), so for the 1b packings bit j of the underlying word is scalar j, and symbol s of the repacked message has scalar
+    //s * DEGREE + k as its k-th base in the tower basis, the same order repack reads an unpacked &[P::Scalar] in.
+    #[instrument(skip_all, name = "encode_packed_subfield", level = "debug")]
+    pub fn new_from_packed_subfield<P, N>(packed: &[P], ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P::Scalar>,
+        P: PackedField,
+        P::Scalar: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let degree = <BinaryField128b as ExtensionField<P::Scalar>>::DEGREE;
+        let num_scalars = packed.len() * P::WIDTH;
+        assert!(
+            num_scalars % degree == 0,
+            "{num_scalars} packed scalars do not fill whole symbols of {degree} scalars"
+        );
+
+        let repacked_message: Vec<BinaryField128b> = (0..num_scalars / degree)
+            .into_par_iter()
+            .map_init(
+                || Vec::with_capacity(degree),
+                |bases, symbol| {
+                    bases.clear();
+                    bases.extend(
+                        (symbol * degree..(symbol + 1) * degree)
+                            .map(|i| packed[i / P::WIDTH].get(i % P::WIDTH)),
+                    );
+                    BinaryField128b::from_bases(bases).unwrap()
+                },
+            )
+            .collect();
+        Self::encode_repacked(&repacked_message, ntt)
+    }
+
+    //Encodes with the widest packed kernel of the build: PackedBinaryField4x128b when compiled with AVX-512F and GFNI enabled, PackedBinaryField2x128b
+    //with AVX2 and GFNI, and the scalar Code::new otherwise or off x86_64. binius picks the backend of its packed fields from target_feature when it is
+    //compiled, not from the CPU at runtime, so a default build gets the scalar path even on an AVX-512 machine; build with
+    //RUSTFLAGS="-C target-cpu=native" (or the matching -C target-feature flags) to use the wide kernels. Every kernel produces the same codeword.
+    #[instrument(skip_all, name = "encode_auto", level = "debug")]
+    pub fn new_auto<F, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+    {
+        if cfg!(all(
+            target_arch = "x86_64",
+            target_feature = "avx512f",
+            target_feature = "gfni"
+        )) {
+            Self::new_packed::<F, PackedBinaryField4x128b, N>(message, ntt)
+        } else if cfg!(all(
+            target_arch = "x86_64",
+            target_feature = "avx2",
+            target_feature = "gfni"
+        )) {
+            Self::new_packed::<F, PackedBinaryField2x128b, N>(message, ntt)
+        } else {
+            Self::new(message, ntt)
+        }
+    }
+
+    //Encodes message with the cosets appended in order instead of 0..RATE. Use coset and idx_in_order to read the result by natural coset and position.
+    #[instrument(skip_all, name = "encode_with_order", level = "debug")]
+    pub fn new_with_order<F, N>(message: &[F], order: CosetOrder, ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+
+        let mut encoding = Vec::with_capacity(repacked_message.len() * RATE);
+        let mut temp;
+
+        for slot in 0..RATE {
+            temp = repacked_message.clone();
+            ntt.forward_transform(&mut temp, order.coset_at(slot) as u32, 0)
+                .unwrap();
+            encoding.append(&mut temp);
+        }
+        Code { encoding }
+    }
+
+    //Code::new for coefficients given in the novel basis, which is the basis Code::new already assumes, so this only makes the basis explicit at the call
+    //site. A polynomial in the monomial basis has to be converted to the novel basis before either of them.
+    pub fn new_novel_basis<N>(coeffs: &[BinaryField128b], ntt: &N) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+    {
+        Self::new(coeffs, ntt)
+    }
+
+    //Code::new together with the evaluations of the message polynomial on the base domain, the span of the first log2(message.len()) basis elements.
+    //Code::new is not systematic, the message holds novel basis coefficients rather than evaluations, but coset 0 of the codeword is that base domain, so
+    //the evaluations are a copy of coset 0 and no extra transform is run.
+    pub fn new_with_message_evals<F, N>(
+        message: &[F],
+        ntt: &N,
+    ) -> (Code<BinaryField128b>, Vec<BinaryField128b>)
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+    {
+        let code = Self::new(message, ntt);
+        let message_evals = code.coset(0, CosetOrder::Natural).to_vec();
+        (code, message_evals)
+    }
+
+    //Code::new that also reports how long the transforms of all cosets took, for a quick throughput number without a profiler. Repacking the message
+    //is not timed.
+    #[instrument(skip_all, name = "encode_with_stats", level = "debug")]
+    pub fn new_with_stats<F, N>(message: &[F], ntt: &N) -> (Code<BinaryField128b>, EncodeStats)
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+
+        let time = Instant::now();
+        let code = Self::encode_repacked(&repacked_message, ntt);
+        let stats = EncodeStats {
+            elapsed: time.elapsed(),
+            symbols: code.encoding.len(),
+        };
+        (code, stats)
+    }
+
+    //Code::new of the message of length message_len whose only nonzero coefficients are nonzeros, as the sum of val * the encoding of the unit vector at
+    //idx. Symbol g costs popcount(idx) products per nonzero while Code::new costs about log2(message_len) / 2 per symbol, so this is only faster while the
+    //popcounts of the nonzero indices sum to less than about log2(message_len) / 2, i.e. for a handful of low-index nonzeros.
+    #[instrument(skip_all, name = "encode_sparse", level = "debug")]
+    pub fn new_sparse<N>(
+        nonzeros: &[(usize, BinaryField128b)],
+        message_len: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        assert!(
+            message_len.is_power_of_two(),
+            "Message length {message_len} is not a power of 2"
+        );
+        assert!(
+            nonzeros.iter().all(|(idx, _)| *idx < message_len),
+            "Nonzero index outside a message of length {message_len}"
+        );
+
+        let encoding = (0..message_len * RATE)
+            .into_par_iter()
+            .map(|g| {
+                nonzeros
+                    .iter()
+                    .map(|(idx, val)| *val * novel_basis_eval(*idx, g, ntt))
+                    .sum()
+            })
+            .collect();
+        Code { encoding }
+    }
+
+    //Encodes message on the listed cosets only, e.g. the systematic coset and one check coset, skipping the transforms of the others. Code assumes all
+    //RATE cosets are present, so the result is a PartialCode that keeps the coset indices to resolve coset and idx.
+    #[instrument(skip_all, name = "encode_cosets", level = "debug")]
+    pub fn new_cosets<F, N>(message: &[F], cosets: &[u32], ntt: &N) -> PartialCode
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        assert!(
+            cosets.iter().all(|c| (*c as usize) < RATE),
+            "Cosets {cosets:?} must lie below {RATE}"
+        );
+
+        let repacked_message = repack(message);
+
+        let mut encoding = Vec::with_capacity(repacked_message.len() * cosets.len());
+        let mut temp;
+
+        for c in cosets.iter() {
             temp = repacked_message.clone();
+            ntt.forward_transform(&mut temp, *c, 0).unwrap();
+            encoding.append(&mut temp);
+        }
+        PartialCode {
+            encoding,
+            cosets: cosets.to_vec(),
+        }
+    }
+
+    //Encodes message and applies the first fold in the same pass: each coset is folded right after its transform, while it is still in cache, so the
+    //full codeword is never materialised. Same output as Code::new(message, ntt).fold_code(r0, 0, ntt).
+    #[instrument(skip_all, name = "encode_and_fold", level = "debug")]
+    pub fn new_and_fold<F, N>(message: &[F], r0: BinaryField128b, ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+        let half_coset_len = repacked_message.len() >> 1;
+
+        let mut encoding = Vec::with_capacity(half_coset_len * RATE);
+        let mut temp = repacked_message.clone();
+
+        for i in 0..RATE {
+            temp.copy_from_slice(&repacked_message);
+            ntt.forward_transform(&mut temp, i as u32, 0).unwrap();
+            encoding.par_extend(
+                temp.par_chunks_exact(2)
+                    .enumerate()
+                    .map(|(j, pair)| fold(r0, 0, i * half_coset_len + j, pair[0], pair[1], ntt)),
+            );
+        }
+        Code { encoding }
+    }
+
+    fn encode_repacked<N>(repacked_message: &[BinaryField128b], ntt: &N) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let mut encoding = Vec::with_capacity(repacked_message.len() * RATE);
+        let mut temp;
+
+        for i in 0..RATE as u32 {
+            temp = repacked_message.to_vec();
             ntt.forward_transform(&mut temp, i, 0).unwrap();
             encoding.append(&mut temp);
         }
@@ -48,41 +612,94 @@ impl Code<BinaryField128b> {
     }
 
     #[instrument(skip_all, name = "encode_ext", level = "debug")]
-    pub fn new_ext<F, P>(message: &[F], ntt: &MultithreadedNTT<P>) -> Code<BinaryField128b>
+    pub fn new_ext<F, P, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
+    where
+        F: EncodableWith<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        F::try_encode_ext(message, ntt).expect("failed to encode message")
+    }
+
+    //Fallible new_ext. The extension bounds between the message field F, the NTT field P and BinaryField128b are checked at compile time. What is left is
+    //checked here before the NTT sees the data: a message not packing into BinaryField128b symbols is UnalignedMessage, a codeword whose domain does not
+    //fit in P at all, so that no NTT over P could encode it, is FieldMismatch, and one that only outgrows this ntt's domain is MessageTooLong.
+    #[instrument(skip_all, name = "try_encode_ext", level = "debug")]
+    pub fn try_new_ext<F, P, N>(message: &[F], ntt: &N) -> Result<Code<BinaryField128b>, CodeError>
     where
         BinaryField128b: ExtensionField<F> + ExtensionField<P> + PackedExtension<P>,
         F: BinaryField + TowerField + ExtensionField<P>,
         P: BinaryField,
+        N: AdditiveNTT<P>,
     {
-        let repacked_message: Vec<BinaryField128b> = message
-            .par_chunks(<BinaryField128b as ExtensionField<F>>::DEGREE)
-            .map(|base_elems| BinaryField128b::from_bases(base_elems).unwrap())
-            .collect();
+        let degree = <BinaryField128b as ExtensionField<F>>::DEGREE;
+        if message.is_empty() || message.len() % degree != 0 {
+            return Err(CodeError::UnalignedMessage {
+                message_len: message.len(),
+                degree,
+            });
+        }
+
+        let message_symbols = message.len() / degree;
+        let log_domain = message_symbols.next_power_of_two().trailing_zeros() as usize + LOG_RATE;
+        let field_bits = <P as ExtensionField<BinaryField1b>>::DEGREE;
+        if log_domain > field_bits {
+            return Err(CodeError::FieldMismatch {
+                log_domain,
+                field_bits,
+            });
+        }
+        if log_domain > ntt.log_domain_size() {
+            return Err(CodeError::MessageTooLong {
+                message_symbols,
+                max_symbols: (1 << ntt.log_domain_size()) / RATE,
+            });
+        }
+
+        let repacked_message = repack(message);
 
         let mut encoding = Vec::with_capacity(repacked_message.len() * RATE);
         let mut temp;
 
         for i in 0..RATE as u32 {
             temp = repacked_message.clone();
-            ntt.forward_transform_ext::<BinaryField128b>(&mut temp, i)
-                .unwrap();
+            ntt.forward_transform_ext::<BinaryField128b>(&mut temp, i)?;
             encoding.append(&mut temp);
         }
-        Code { encoding }
+        Ok(Code { encoding })
     }
 
+    //The NTT is taken per call, so protocols can pass a different NTT in every round. round is always the absolute round: the twiddles of round k
+    //are evaluations of the k-th normalised subspace polynomial of the NTT's basis, so a per-round NTT must be over the same basis and be indexed with k,
+    //not 0. An NTT over a different basis changes the evaluation domain and the folded layer is no longer a codeword of the next round.
+    //self is only read, so one encoded codeword can be folded under any number of independent challenges without re-encoding.
     #[instrument(skip_all, name = "fold code", level = "debug")]
-    pub fn fold_code<P>(
+    pub fn fold_code<P, N>(
         &self,
         r: BinaryField128b, //folding challenge
         round: usize,
-        ntt: &MultithreadedNTT<P>,
+        ntt: &N,
     ) -> Code<BinaryField128b>
     where
         BinaryField128b: ExtensionField<P>,
         P: BinaryField,
+        N: AdditiveNTT<P>,
     {
         let mut encoding: Vec<BinaryField128b> = vec![BinaryField128b::ZERO; self.encoding.len() >> 1];
+        //The last pair bounds every pair index, so checking it once covers the loop
+        debug_assert!(
+            encoding.is_empty() || checked_pair_indices(encoding.len() - 1).is_ok(),
+            "Pair indices of a codeword of length {} overflow",
+            self.encoding.len()
+        );
+        //A layer folded in round has at most 2^(log_domain - round) symbols, larger ones would read twiddles of pairs outside the domain
+        assert!(
+            self.encoding.is_empty()
+                || round + (self.encoding.len().trailing_zeros() as usize) <= ntt.log_domain_size(),
+            "Codeword of length {} cannot be folded in round {round} of a domain of size 2^{}",
+            self.encoding.len(),
+            ntt.log_domain_size()
+        );
 
         encoding.par_iter_mut().enumerate().for_each(|(i, val)| {
             *val = fold(
@@ -97,86 +714,4834 @@ impl Code<BinaryField128b> {
         Code { encoding }
     }
 
-    pub fn idx(&self, idx: usize) -> BinaryField128b {
-        self.encoding[idx]
-    }
-}
-
-#[inline(always)]
-pub fn fold<P>(
-    r: BinaryField128b,
-    round: usize,
-    idx: usize,
-    val0: BinaryField128b,
-    val1: BinaryField128b,
-    ntt: &MultithreadedNTT<P>,
-) -> BinaryField128b
-where
-    BinaryField128b: ExtensionField<P>,
-    P: BinaryField,
-{
-    //twiddle for inverse ntt component of the fold i.e the twiddle for the butterfly unit
-    //at the index if we were to apply the inverse ntt
+    //fold_code written into out, which must hold exactly encoding.len() / 2 symbols, so a loop of folds can reuse one scratch buffer sized for the first
+    //round and pass &mut buf[..len / 2] each round.
+    #[instrument(skip_all, name = "fold code into buf", level = "debug")]
+    pub fn fold_code_into_buf<P, N>(
+        &self,
+        r: BinaryField128b,
+        round: usize,
+        ntt: &N,
+        out: &mut [BinaryField128b],
+    ) -> Result<(), CodeError>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        if out.len() != self.encoding.len() >> 1 {
+            return Err(CodeError::OutputLength {
+                expected: self.encoding.len() >> 1,
+                actual: out.len(),
+            });
+        }
 
-    let twiddle = ntt.get_subspace_eval(round, idx);
-    let (mut x0, mut x1) = (val0, val1);
-    x1 += x0;
-    x0 += x1 * twiddle;
+        out.par_iter_mut().enumerate().for_each(|(i, val)| {
+            *val = fold(
+                r,
+                round,
+                i,
+                self.encoding[i << 1],
+                self.encoding[(i << 1) | 1],
+                ntt,
+            )
+        });
+        Ok(())
+    }
 
-    x0 + r * (x0 + x1)
-}
+    //fold_code over the len symbols of source instead of an owned codeword, e.g. a verifier reading leaves that are authenticated as they are read.
+    #[instrument(skip_all, name = "fold code from", level = "debug")]
+    pub fn fold_code_from<S, P, N>(
+        source: &S,
+        len: usize,
+        r: BinaryField128b,
+        round: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        S: SymbolSource + Sync,
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(len % 2 == 0, "Cannot fold a source of odd length {len}");
 
-#[test]
-fn test_fold() {
-    let l = 11;
-    let poly: Vec<BinaryField128b> = (0..1 << l)
-        .into_par_iter()
-        .map(|_| BinaryField128b::random(thread_rng()))
-        .collect();
+        let encoding = (0..len >> 1)
+            .into_par_iter()
+            .map(|i| {
+                fold(
+                    r,
+                    round,
+                    i,
+                    source.get(i << 1),
+                    source.get((i << 1) | 1),
+                    ntt,
+                )
+            })
+            .collect();
+        Code { encoding }
+    }
 
-    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + 2)
-        .unwrap()
-        .multithreaded();
+    //fold_code with a challenge sampled in a subfield S, folding every pair with fold_subfield.
+    pub fn fold_code_ext<S, P, N>(&self, r: S, round: usize, ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<S> + ExtensionField<P>,
+        S: BinaryField,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let encoding = self
+            .encoding
+            .par_chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| fold_subfield(r, round, i, pair[0], pair[1], ntt))
+            .collect();
+        Code { encoding }
+    }
 
-    let code = Code::new(&poly, &ntt);
+    //fold_code with the twiddles read from schedule instead of the NTT, schedule must cover round.
+    #[instrument(skip_all, name = "fold code cached", level = "debug")]
+    pub fn fold_code_cached(
+        &self,
+        r: BinaryField128b,
+        round: usize,
+        schedule: &FoldSchedule,
+    ) -> Code<BinaryField128b> {
+        let encoding = self
+            .encoding
+            .par_chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| fold_scheduled(r, round, i, pair[0], pair[1], schedule))
+            .collect();
+        Code { encoding }
+    }
 
+    //Folds the codeword in the same variable as eq, the eq table of the remaining challenges with r as its lowest variable. Keeping both in lockstep preserves
+    //eq.evaluate(folded_code.decode_at_round(round + 1, ntt)) == fold_all(challenges), which makes every intermediate layer checkable against the claim.
+    #[instrument(skip_all, name = "fold code with eq", level = "debug")]
+    pub fn fold_code_with_eq<P, N>(
+        &self,
+        r: BinaryField128b,
+        round: usize,
+        ntt: &N,
+        eq: &mut LagrangeBases,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert_eq!(
+            eq.vals.len(),
+            self.encoding.len() / RATE,
+            "Eq table does not match the dimension of the codeword"
+        );
+
+        eq.fold_in();
+        self.fold_code(r, round, ntt)
+    }
+
+    //Folds the codeword until its length is target_len, consuming one challenge per round starting at round 0.
+    //Used to stop FRI early and send the remaining coefficients directly.
+    #[instrument(skip_all, name = "fold to size", level = "debug")]
+    pub fn fold_to_size<P, N>(
+        &self,
+        challenges: &[BinaryField128b],
+        target_len: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            target_len.is_power_of_two(),
+            "Target length {target_len} is not a power of 2"
+        );
+        assert!(
+            target_len >= RATE && target_len <= self.encoding.len(),
+            "Target length {target_len} must lie between {RATE} and {}",
+            self.encoding.len()
+        );
+
+        let rounds = (self.encoding.len() / target_len).trailing_zeros() as usize;
+        assert_eq!(
+            challenges.len(),
+            rounds,
+            "Folding to length {target_len} needs exactly {rounds} challenges"
+        );
+
+        self.fold_rounds(challenges, 0, ntt)
+    }
+
+    //Folds by 2^log_arity in one call with the challenges r, r^2, r^4, .., r^(2^(log_arity - 1)) for rounds round..round + log_arity, so one sample
+    //from the transcript serves the whole arity. The folded layer is then multilinear in these powers, a univariate polynomial of degree up to
+    //2^log_arity - 1 in r, so by Schwartz-Zippel a bad fold survives with probability up to (2^log_arity - 1) / |F| instead of log_arity / |F| for
+    //independent challenges. Over BinaryField128b both are negligible for any practical arity.
+    #[instrument(skip_all, name = "fold code power", level = "debug")]
+    pub fn fold_code_power<P, N>(
+        &self,
+        r: BinaryField128b,
+        round: usize,
+        log_arity: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let challenges: Vec<BinaryField128b> = (0..log_arity)
+            .scan(r, |power, _| {
+                let challenge = *power;
+                *power = *power * *power;
+                Some(challenge)
+            })
+            .collect();
+        self.fold_rounds(&challenges, round, ntt)
+    }
+
+    //fold_code(r0, round).fold_code(r1, round + 1) in one pass: each quadruple 4j..4j + 3 folds to the pair (2j, 2j + 1) of the middle layer under r0
+    //and that pair straight to symbol j under r1, so the input is read once and the half-length layer is never stored.
+    #[instrument(skip_all, name = "fold2", level = "debug")]
+    pub fn fold2<P, N>(
+        &self,
+        r0: BinaryField128b,
+        r1: BinaryField128b,
+        round: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            self.encoding.len() >= 4,
+            "Cannot fold a codeword of length {} twice",
+            self.encoding.len()
+        );
+        assert!(
+            round + (self.encoding.len().trailing_zeros() as usize) <= ntt.log_domain_size(),
+            "Codeword of length {} cannot be folded in round {round} of a domain of size 2^{}",
+            self.encoding.len(),
+            ntt.log_domain_size()
+        );
+
+        let encoding = self
+            .encoding
+            .par_chunks_exact(4)
+            .enumerate()
+            .map(|(j, quad)| {
+                let left = fold(r0, round, 2 * j, quad[0], quad[1], ntt);
+                let right = fold(r0, round, 2 * j + 1, quad[2], quad[3], ntt);
+                fold(r1, round + 1, j, left, right, ntt)
+            })
+            .collect();
+        Code { encoding }
+    }
+
+    //Applies challenges.len() folds to a layer reached after start_round folds, indexing the twiddles by absolute round. Lets one party fold rounds
+    //0..k and another continue from the returned layer at round k.
+    #[instrument(skip_all, name = "fold rounds", level = "debug")]
+    pub fn fold_rounds<P, N>(
+        &self,
+        challenges: &[BinaryField128b],
+        start_round: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            self.encoding.len() >> challenges.len() >= RATE,
+            "Cannot fold a codeword of length {} {} times",
+            self.encoding.len(),
+            challenges.len()
+        );
+
+        let mut folded_code = self.clone();
+        for (i, r) in challenges.iter().enumerate() {
+            folded_code = folded_code.fold_code(*r, start_round + i, ntt);
+        }
+        folded_code
+    }
+
+    //The layers fold_rounds(challenges, 0, ntt) passes through, yielded by value without copies. The iterator folds one layer ahead: next hands out the
+    //layer it holds and keeps its fold for the following call, so a caller that drops every layer before advancing has at most two layers alive, the
+    //one it holds and the next. The codeword itself is dropped once the first layer is out.
+    pub fn fold_layers_lazy<'a, P, N>(
+        self,
+        challenges: &'a [BinaryField128b],
+        ntt: &'a N,
+    ) -> impl Iterator<Item = Code<BinaryField128b>> + 'a
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            self.encoding.len() >> challenges.len() >= RATE,
+            "Cannot fold a codeword of length {} {} times",
+            self.encoding.len(),
+            challenges.len()
+        );
+
+        let mut rounds = challenges.iter().enumerate();
+        iter::successors(Some(self), move |layer| {
+            rounds
+                .next()
+                .map(|(round, r)| layer.fold_code(*r, round, ntt))
+        })
+        .skip(1)
+    }
+
+    //fold_all at every point, reading the codeword once. The round 0 butterflies do not depend on the challenge, so they are computed once as the pairs
+    //(x0, x0 + x1) and each point's first layer is x0 + r * (x0 + x1), one multiplication per pair instead of two. From round 1 on the layers of different
+    //points differ and fold separately, so for k points this saves k - 1 passes of twiddle multiplications over the largest layer, about a quarter of the
+    //work of each extra evaluation.
+    #[instrument(skip_all, name = "fold all multi", level = "debug")]
+    pub fn fold_all_multi<P, N>(
+        &self,
+        points: &[&[BinaryField128b]],
+        ntt: &N,
+    ) -> Vec<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let rounds = self.required_challenges();
+        assert!(
+            points.iter().all(|point| point.len() == rounds),
+            "Every point must have {rounds} coordinates to fold a codeword of length {}",
+            self.encoding.len()
+        );
+
+        let butterflies: Vec<(BinaryField128b, BinaryField128b)> = self
+            .encoding
+            .par_chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let x1 = pair[0] + pair[1];
+                let x0 = pair[0] + x1 * fold_twiddle(0, i, ntt);
+                (x0, x0 + x1)
+            })
+            .collect();
+
+        points
+            .iter()
+            .map(|point| {
+                let encoding = butterflies
+                    .par_iter()
+                    .map(|(x0, diff)| *x0 + point[0] * *diff)
+                    .collect();
+                Code { encoding }.fold_rounds(&point[1..], 1, ntt).idx(0)
+            })
+            .collect()
+    }
+
+    //Bytes held by the symbols of the codeword, excluding the Vec's spare capacity.
+    pub fn footprint(&self) -> usize {
+        self.encoding.len() * size_of::<BinaryField128b>()
+    }
+
+    //bind_header of the commit_oracle root under the codeword's own message_dim and rate, laid out in coset_order. The same symbols declared with
+    //other parameters give another digest.
+    pub fn digest(&self, coset_order: CosetOrder) -> Hash {
+        let header = CodeHeader {
+            message_dim: self.message_dim(),
+            rate: self.rate(),
+            coset_order,
+        };
+        bind_header(&MerkleTree::build(&self.encoding, 2).get_root(), &header)
+    }
+
+    //The derived parameters of the codeword in one struct, for bug reports and benchmark logs.
+    pub fn describe(&self) -> CodeDescription {
+        CodeDescription {
+            message_dim: self.message_dim(),
+            codeword_len: self.encoding.len(),
+            rate: self.rate(),
+            num_cosets: self.encoding.len() >> self.message_dim(),
+            footprint_bytes: self.footprint(),
+            num_fold_rounds: self.required_challenges(),
+        }
+    }
+
+    //fold_all under a name that spells out that self is left untouched: a prover opening one polynomial at several points encodes it once and calls
+    //this once per challenge sequence, each folding a fresh copy of the layers. fold_all_multi also shares the first round between the points.
+    pub fn fold_all_fresh<P, N>(&self, challenges: &[BinaryField128b], ntt: &N) -> BinaryField128b
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        self.fold_all(challenges, ntt)
+    }
+
+    //Number of challenges fold_all and the methods built on it take, one per round down to RATE symbols: log2(len) - LOG_RATE.
+    pub fn required_challenges(&self) -> usize {
+        (self.encoding.len() / RATE).trailing_zeros() as usize
+    }
+
+    //Up front check of a challenge vector for the fold_all family, so a wrong count fails before any layer is folded instead of leaving a partial fold.
+    pub fn check_challenge_count(&self, challenges: &[BinaryField128b]) -> Result<(), CodeError> {
+        let expected = self.required_challenges();
+        match challenges.len() == expected {
+            true => Ok(()),
+            false => Err(CodeError::ChallengeCountMismatch {
+                got: challenges.len(),
+                expected,
+            }),
+        }
+    }
+
+    //Folds the codeword all the way down to RATE symbols and returns the final value, which is the multilinear evaluation of the message at challenges.
+    //Panics unless challenges.len() == required_challenges(), fold_all_checked returns the mismatch instead.
+    #[instrument(skip_all, name = "fold all", level = "debug")]
+    pub fn fold_all<P, N>(&self, challenges: &[BinaryField128b], ntt: &N) -> BinaryField128b
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        if let Err(error) = self.check_challenge_count(challenges) {
+            panic!("{error}");
+        }
+        self.fold_to_size(challenges, RATE, ntt).idx(0)
+    }
+
+    //fold_all that also chains every layer into one digest, self first and the RATE symbol final layer last: running starts at H::initial() and
+    //becomes H::chain(running, H::layer_digest(layer)) for each layer in turn. With MerkleRootHasher a layer digest is the layer's Merkle root, so a
+    //verifier recomputes the chain from the commitment root, the layer roots and the final layer and compares it with the prover's digest.
+    #[instrument(skip_all, name = "fold_all_hashed", level = "debug")]
+    pub fn fold_all_hashed<H, P, N>(
+        &self,
+        challenges: &[BinaryField128b],
+        ntt: &N,
+    ) -> (BinaryField128b, H::Digest)
+    where
+        H: SymbolHasher,
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        if let Err(error) = self.check_challenge_count(challenges) {
+            panic!("{error}");
+        }
+
+        let mut running = H::chain(&H::initial(), &H::layer_digest(&self.encoding));
+        let mut folded_code = self.clone();
+        for (round, &r) in challenges.iter().enumerate() {
+            folded_code = folded_code.fold_code(r, round, ntt);
+            running = H::chain(&running, &H::layer_digest(&folded_code.encoding));
+        }
+        (folded_code.idx(0), running)
+    }
+
+    //Sums the lowest drop_vars variables of the message out, the variables fold consumes first: the result is the layer at round drop_vars whose message
+    //is m'[i] = sum_j m[(i << drop_vars) + j] over j < 2^drop_vars. Summing a variable out is fold(ZERO) + fold(ONE), i.e. x0 + x1 of every butterflied
+    //pair, while projecting it to 0 or 1 is fold_code with ZERO or ONE. Like any layer it is decoded with decode_at_round(drop_vars, ntt).
+    #[instrument(skip_all, name = "restrict", level = "debug")]
+    pub fn restrict<P, N>(&self, drop_vars: usize, ntt: &N) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            self.encoding.len() >> drop_vars >= RATE,
+            "Cannot sum {drop_vars} variables out of a codeword of length {}",
+            self.encoding.len()
+        );
+
+        let mut restricted_code = self.clone();
+        for round in 0..drop_vars {
+            let encoding = restricted_code
+                .encoding
+                .par_chunks_exact(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    let x1 = pair[0] + pair[1];
+                    let x0 = pair[0] + x1 * fold_twiddle(round, i, ntt);
+                    x0 + x1
+                })
+                .collect();
+            restricted_code = Code { encoding };
+        }
+        restricted_code
+    }
+
+    //Sum over all pairs of x0 + x1, with (x0, x1) the butterflied pair that fold combines as x0 + r * (x0 + x1) at round. As fold is affine in r this is
+    //the sum of fold(ONE) - fold(ZERO) over the layer, the coefficient of r in the sum of the folded layer, so it can be fed into a sum check round polynomial.
+    #[instrument(skip_all, name = "fold sum", level = "debug")]
+    pub fn fold_sum<P, N>(&self, round: usize, ntt: &N) -> BinaryField128b
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        self.encoding
+            .par_chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let x1 = pair[0] + pair[1];
+                let x0 = pair[0] + x1 * fold_twiddle(round, i, ntt);
+                x0 + x1
+            })
+            .sum()
+    }
+
+    //Replaces the message coefficient at message_idx, old_val, by new_val without re-encoding. The encoding is linear, so the codeword changes by
+    //(new_val - old_val) times the encoding of the unit vector at message_idx, the novel basis polynomial X_message_idx = prod Ŝ_i over the set bits i
+    //of message_idx evaluated over the domain. Each symbol costs popcount(message_idx) products instead of the log(len) butterflies per symbol of Code::new.
+    #[instrument(skip_all, name = "update symbol", level = "debug")]
+    pub fn update_symbol<N>(
+        &mut self,
+        message_idx: usize,
+        old_val: BinaryField128b,
+        new_val: BinaryField128b,
+        ntt: &N,
+    ) where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let log_dim = self.message_dim();
+        assert!(
+            message_idx < 1 << log_dim,
+            "Message index {message_idx} is outside a message of {} coefficients",
+            1 << log_dim
+        );
+
+        let delta = new_val - old_val;
+        self.encoding
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(g, val)| *val += delta * novel_basis_eval(message_idx, g, ntt));
+    }
+
+    //Prover side self test before sending a proof: checks the fold relation layers[k + 1][i] == fold of layers[k] at i under challenges[k] in round k
+    //at num_samples random i per round. Catches a bug in the prover's folding, a corrupted layer passes only if no sampled position hits it.
+    #[instrument(skip_all, name = "self check", level = "debug")]
+    pub fn self_check<P, N>(
+        layers: &[Code<BinaryField128b>],
+        challenges: &[BinaryField128b],
+        num_samples: usize,
+        ntt: &N,
+    ) -> Result<(), CodeError>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert_eq!(
+            layers.len(),
+            challenges.len() + 1,
+            "Folding {} times needs {} layers",
+            challenges.len(),
+            challenges.len() + 1
+        );
+
+        let mut rng = thread_rng();
+        for (round, (r, pair)) in challenges.iter().zip(layers.windows(2)).enumerate() {
+            let (layer, folded_layer) = (&pair[0], &pair[1]);
+            assert_eq!(
+                folded_layer.encoding.len() << 1,
+                layer.encoding.len(),
+                "Layer of round {round} is not half as long as the one before"
+            );
+
+            for _ in 0..num_samples {
+                let idx = rng.gen_range(0..folded_layer.encoding.len());
+                let folded = fold(
+                    *r,
+                    round,
+                    idx,
+                    layer.idx(idx << 1),
+                    layer.idx((idx << 1) | 1),
+                    ntt,
+                );
+                if folded != folded_layer.idx(idx) {
+                    return Err(CodeError::InconsistentFold { round, idx });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //Stores the symbols one bit each, for words whose symbols all lie in the BinaryField1b subfield, i.e. are ZERO or ONE. This holds for raw bit data
+    //but not for the codeword of a bit message, whose symbols are spread over the NTT field by the twiddles, so such a codeword is rejected with
+    //NotInSubfield at its first symbol that is not a bit.
+    pub fn pack_bits(&self) -> Result<PackedBitCommitment, CodeError> {
+        let mut bits = vec![0u64; self.encoding.len().div_ceil(64)];
+        for (idx, symbol) in self.encoding.iter().enumerate() {
+            match symbol.val() {
+                0 => {}
+                1 => bits[idx / 64] |= 1 << (idx % 64),
+                _ => return Err(CodeError::NotInSubfield { idx }),
+            }
+        }
+        Ok(PackedBitCommitment {
+            bits,
+            len: self.encoding.len(),
+        })
+    }
+
+    //De-interleaves the codeword into its even and odd indexed symbols, i.e. the two halves of every fold pair as contiguous vectors.
+    pub fn even_odd(&self) -> (Vec<BinaryField128b>, Vec<BinaryField128b>) {
+        self.encoding
+            .par_chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .unzip()
+    }
+
+    //Serializes the codeword as the 16 byte little-endian representation of each symbol.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encoding
+            .iter()
+            .flat_map(|symbol| symbol.val().to_le_bytes())
+            .collect()
+    }
+
+    //Deserializes an untrusted codeword that must encode a message of expected_dim variables at inverse rate rate. Any other length is rejected before
+    //a symbol is read, so a prover cannot substitute a codeword of a different size.
+    pub fn from_bytes_expecting(
+        bytes: &[u8],
+        expected_dim: usize,
+        rate: usize,
+    ) -> Result<Code<BinaryField128b>, CodeError> {
+        let expected = (1 << expected_dim) * rate * size_of::<u128>();
+        if bytes.len() != expected {
+            return Err(CodeError::UnexpectedLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let encoding = bytes
+            .chunks_exact(size_of::<u128>())
+            .map(|symbol| BinaryField128b::new(u128::from_le_bytes(symbol.try_into().unwrap())))
+            .collect();
+        Ok(Code { encoding })
+    }
+
+    //fold_all for protocols that assume nonzero folding challenges. With require_nonzero set, a ZERO challenge, which silently discards the odd half
+    //of the fold, is rejected before any folding is done. A challenge count other than required_challenges() is always rejected.
+    pub fn fold_all_checked<P, N>(
+        &self,
+        challenges: &[BinaryField128b],
+        require_nonzero: bool,
+        ntt: &N,
+    ) -> Result<BinaryField128b, CodeError>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        self.check_challenge_count(challenges)?;
+        let zero_round = challenges
+            .iter()
+            .position(|r| require_nonzero && *r == BinaryField128b::ZERO);
+        if let Some(round) = zero_round {
+            return Err(CodeError::ZeroChallenge { round });
+        }
+        Ok(self.fold_all(challenges, ntt))
+    }
+
+    pub fn idx(&self, idx: usize) -> BinaryField128b {
+        self.encoding[idx]
+    }
+
+    //Symbols of coset c of a codeword laid out in order.
+    pub fn coset(&self, c: usize, order: CosetOrder) -> &[BinaryField128b] {
+        let coset_len = self.encoding.len() / RATE;
+        let slot = order.coset_at(c);
+        &self.encoding
+            [checked_coset_range(slot, coset_len).unwrap_or_else(|error| panic!("{error}"))]
+    }
+
+    //Symbol at natural position idx of a codeword laid out in order.
+    pub fn idx_in_order(&self, idx: usize, order: CosetOrder) -> BinaryField128b {
+        self.encoding[order.position(idx, self.encoding.len() / RATE)]
+    }
+
+    //Checks whether other holds the same RATE cosets as self in some order, e.g. the same codeword laid out in a different CosetOrder. Two empty words
+    //are equal, and words whose length does not split into RATE cosets are never equal since they have no cosets to permute.
+    pub fn eq_up_to_coset_perm(&self, other: &Code<BinaryField128b>) -> bool {
+        if self.encoding.len() != other.encoding.len() {
+            return false;
+        }
+        if self.encoding.is_empty() {
+            return true;
+        }
+        if self.encoding.len() % RATE != 0 {
+            return false;
+        }
+
+        let coset_len = self.encoding.len() / RATE;
+        let mut matched = [false; RATE];
+        self.encoding.chunks(coset_len).all(|coset| {
+            other
+                .encoding
+                .chunks(coset_len)
+                .zip(matched.iter_mut())
+                .find(|(other_coset, matched)| !**matched && *other_coset == coset)
+                .map(|(_, matched)| *matched = true)
+                .is_some()
+        })
+    }
+
+    //Number of variables of the encoded message, i.e. log2(encoding.len() / RATE). Panics on a codeword shorter than RATE.
+    pub fn message_dim(&self) -> usize {
+        let len = self.encoding.len();
+        assert!(
+            len >= RATE,
+            "Codeword of {len} symbols is shorter than {RATE}"
+        );
+        debug_assert!(
+            len.is_power_of_two(),
+            "Codeword of {len} symbols is not a power of 2"
+        );
+        len.trailing_zeros() as usize - LOG_RATE
+    }
+
+    //Sum of all symbols. The symbols of one coset sum to the highest novel basis coefficient of the message (the inverse transform's odd outputs are plain sums),
+    //so with an even RATE the checksum of any valid codeword is zero and a nonzero checksum flags a corrupted symbol.
+    pub fn checksum(&self) -> BinaryField128b {
+        self.encoding.par_iter().copied().sum()
+    }
+
+    //Inverse rate of the code, i.e. the number of cosets in the encoding.
+    pub fn rate(&self) -> usize {
+        RATE
+    }
+
+    //Codeword of the DEEP-FRI quotient (f(x) - f(z)) / (x - z) for an out-of-domain point z, computed pointwise from the codeword of f and the claimed value f_z = f(z).
+    #[instrument(skip_all, name = "quotient", level = "debug")]
+    pub fn quotient<P, N>(
+        &self,
+        z: BinaryField128b,
+        f_z: BinaryField128b,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let mut denominators: Vec<BinaryField128b> = (0..self.encoding.len())
+            .into_par_iter()
+            .map(|i| {
+                let denominator = Self::domain_point::<P, N>(i, ntt) - z;
+                assert!(
+                    denominator != BinaryField128b::ZERO,
+                    "Quotient point lies in the evaluation domain at index {i}"
+                );
+                denominator
+            })
+            .collect();
+        //One inversion per chunk instead of per symbol, chunks keep the work parallel
+        denominators
+            .par_chunks_mut(BATCH_INVERSE_CHUNK)
+            .for_each(batch_inverse);
+
+        let encoding = self
+            .encoding
+            .par_iter()
+            .zip(denominators.par_iter())
+            .map(|(val, inv_denominator)| (*val - f_z) * *inv_denominator)
+            .collect();
+        Code { encoding }
+    }
+
+    //Codeword of the difference f(x) - f(next(x)) with next(x) = x + β_0 = x + 1, the successor that flips the lowest bit of a domain index, so symbol
+    //idx of the result is self[idx] + self[idx ^ 1] and next is an involution pairing exactly the fold pairs. Ŝ_0(1) = 1 and Ŝ_i(1) = 0 for i > 0, so
+    //by the expansion in shift the difference has coefficient m[2i + 1] at X_2i and zero at every odd index: the novel basis analogue of a derivative.
+    //No NTT is needed, the domain only enters through next being addition of β_0.
+    pub fn difference(&self) -> Code<BinaryField128b> {
+        let encoding = self
+            .encoding
+            .par_chunks_exact(2)
+            .flat_map_iter(|pair| {
+                let diff = pair[0] + pair[1];
+                [diff, diff]
+            })
+            .collect();
+        Code { encoding }
+    }
+
+    //Inverse of MerkleTree::build(&code.encoding, leaf_arity): concatenates the symbols of the leaves in leaf order. Meant for the leaves of a small
+    //layer the verifier has opened in full and checked against the root, e.g. to run final_check on it.
+    pub fn from_leaves<L>(leaves: &[L], leaf_arity: usize) -> Code<BinaryField128b>
+    where
+        L: AsRef<[BinaryField128b]>,
+    {
+        assert!(
+            leaves.iter().all(|leaf| leaf.as_ref().len() == leaf_arity),
+            "Every leaf must hold {leaf_arity} symbols"
+        );
+        let len = leaves.len() * leaf_arity;
+        assert!(
+            len.is_power_of_two() && len >= RATE,
+            "{len} symbols do not form a codeword, the length must be a power of 2 of at least {RATE}"
+        );
+
+        let mut encoding = Vec::with_capacity(len);
+        for leaf in leaves {
+            encoding.extend_from_slice(leaf.as_ref());
+        }
+        Code { encoding }
+    }
+
+    //Codeword of g(x) = f(x + c) for the polynomial f this codeword encodes. c is any field element, not only a point of the domain. The Ŝ_i are
+    //F_2-linear, so each novel basis polynomial X_j(x + c) = prod_{i in j} (Ŝ_i(x) + Ŝ_i(c)) expands into X_k(x) for the subsets k of j and g keeps
+    //the degree of f. When c is the domain point of some index k the shift only permutes the symbols, symbol idx of the result is symbol idx ^ k of self.
+    #[instrument(skip_all, name = "shift", level = "debug")]
+    pub fn shift<N>(&self, c: BinaryField128b, ntt: &N) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let shifted_message = shift_novel_coeffs(&self.decode(ntt), c, ntt);
+        Self::encode_repacked(&shifted_message, ntt)
+    }
+
+    //Codeword of the message with each listed variable fixed to its bit, variable i being bit i of the message index. The remaining variables keep their
+    //order, so fixing k variables gives a codeword RATE-times the length of a message in message_dim - k variables, on the base domain. Folding with
+    //challenge b in {0, 1} fixes variable 0 of the message to b in the same way, but the folded layer lives on the domain of round 1, not the base one.
+    #[instrument(skip_all, name = "restrict hypercube", level = "debug")]
+    pub fn restrict_hypercube<N>(&self, fixed: &[(usize, bool)], ntt: &N) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let message = self.decode(ntt);
+        let message_dim = message.len().trailing_zeros() as usize;
+
+        let mut mask = 0;
+        let mut bits = 0;
+        for &(var, bit) in fixed {
+            assert!(
+                var < message_dim,
+                "Variable {var} out of range for a message in {message_dim} variables"
+            );
+            assert_eq!((mask >> var) & 1, 0, "Variable {var} fixed twice");
+            mask |= 1 << var;
+            bits |= (bit as usize) << var;
+        }
+
+        let restricted: Vec<BinaryField128b> = (0..message.len())
+            .filter(|i| i & mask == bits)
+            .map(|i| message[i])
+            .collect();
+        Self::encode_repacked(&restricted, ntt)
+    }
+
+    //Codeword of the message with the Frobenius map x -> x^(2^power) applied to every novel basis coefficient, i.e. of sum_j m[j]^(2^power) * X_j. The
+    //map is F_2-linear but the twiddles are not in F_2, so it does not commute with the NTT and the codeword is decoded and encoded again. The Frobenius
+    //map of BinaryField128b has order 128, so power is taken mod 128.
+    #[instrument(skip_all, name = "frobenius", level = "debug")]
+    pub fn frobenius<N>(&self, power: u32, ntt: &N) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let mut message = self.decode(ntt);
+        message.par_iter_mut().for_each(|coeff| {
+            for _ in 0..power % 128 {
+                *coeff = *coeff * *coeff;
+            }
+        });
+        Self::encode_repacked(&message, ntt)
+    }
+
+    //Erasure decoding: the message of 2^message_dim coefficients whose codeword has the known symbols, given as (position, symbol). The first 2^message_dim
+    //distinct positions are interpolated with the barycentric Lagrange formula onto the points of coset 0, in O(4^message_dim) multiplications, and
+    //coset 0 is then decoded. Further known symbols, and repeats of a position, are not read, but every position must lie in the codeword.
+    #[instrument(skip_all, name = "interpolate from", level = "debug")]
+    pub fn interpolate_from<N>(
+        known: &[(usize, BinaryField128b)],
+        message_dim: usize,
+        ntt: &N,
+    ) -> Result<Vec<BinaryField128b>, CodeError>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        if message_dim >= usize::BITS as usize - LOG_RATE {
+            return Err(CodeError::DimensionOverflow { message_dim });
+        }
+        let needed = 1 << message_dim;
+        let codeword_len = needed * RATE;
+
+        let mut seen = vec![false; codeword_len];
+        let mut points = Vec::with_capacity(needed);
+        for &(idx, symbol) in known {
+            if idx >= codeword_len {
+                return Err(CodeError::PositionOutOfRange {
+                    position: idx,
+                    len: codeword_len,
+                });
+            }
+            if points.len() < needed && !seen[idx] {
+                seen[idx] = true;
+                points.push((BinaryField128b::new(idx as u128), symbol));
+            }
+        }
+        if points.len() < needed {
+            return Err(CodeError::TooFewSymbols {
+                got: points.len(),
+                needed,
+            });
+        }
+
+        //Barycentric weights w_k = 1 / prod_{m != k} (x_k - x_m), the positions are distinct so the products are nonzero
+        let mut weights: Vec<BinaryField128b> = points
+            .par_iter()
+            .map(|(x_k, _)| {
+                points
+                    .iter()
+                    .filter(|(x_m, _)| x_m != x_k)
+                    .map(|(x_m, _)| *x_k - *x_m)
+                    .product()
+            })
+            .collect();
+        batch_inverse(&mut weights);
+
+        //f(t) = prod_k (t - x_k) * sum_k w_k y_k / (t - x_k) at the points t of coset 0, or the known symbol when t is a known position
+        let mut coset_evals: Vec<BinaryField128b> = (0..needed)
+            .into_par_iter()
+            .map(|t| {
+                let x = BinaryField128b::new(t as u128);
+                if seen[t] {
+                    return points.iter().find(|(x_k, _)| *x_k == x).unwrap().1;
+                }
+                let mut inv_diffs: Vec<BinaryField128b> =
+                    points.iter().map(|(x_k, _)| x - *x_k).collect();
+                let vanishing: BinaryField128b = inv_diffs.iter().copied().product();
+                batch_inverse(&mut inv_diffs);
+                vanishing
+                    * points
+                        .iter()
+                        .zip(weights.iter().zip(inv_diffs.iter()))
+                        .map(|((_, y_k), (w_k, inv_diff))| *y_k * *w_k * *inv_diff)
+                        .sum::<BinaryField128b>()
+            })
+            .collect();
+
+        ntt.inverse_transform(&mut coset_evals, 0, 0)?;
+        Ok(coset_evals)
+    }
+
+    //Recovers the repacked message of a valid codeword by inverting the transform of coset 0.
+    #[instrument(skip_all, name = "decode", level = "debug")]
+    pub fn decode<N>(&self, ntt: &N) -> Vec<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let mut message = self.encoding[..self.encoding.len() / RATE].to_vec();
+        ntt.inverse_transform(&mut message, 0, 0).unwrap();
+        message
+    }
+
+    //Checks that self is a codeword whose message lies in the subfield F, i.e. every decoded symbol is the tower embedding of an element of F. The tower
+    //basis embeds F as the symbols whose bits above its lowest 128 / DEGREE are all zero. A word off the code is rejected too: decode only reads coset 0, so
+    //it alone would accept any word whose first coset happens to decode into F.
+    #[instrument(skip_all, name = "verify_subfield", level = "debug")]
+    pub fn verify_subfield<F, N>(&self, ntt: &N) -> bool
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let subfield_bits = 128 / <BinaryField128b as ExtensionField<F>>::DEGREE as u32;
+        self.is_valid_codeword(ntt)
+            && self
+                .decode(ntt)
+                .par_iter()
+                .all(|symbol| symbol.val().checked_shr(subfield_bits).unwrap_or(0) == 0)
+    }
+
+    //Recovers the message of a layer obtained after round folds. The layer is the transform of its message over the folded domain, whose butterflies at
+    //layer i use the twiddles of round + i, so it is inverted over its full length and the coefficients past encoding.len() / RATE are zero.
+    #[instrument(skip_all, name = "decode at round", level = "debug")]
+    pub fn decode_at_round<P, N>(&self, round: usize, ntt: &N) -> Vec<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let mut coeffs = self.encoding.clone();
+        let log_len = coeffs.len().trailing_zeros() as usize;
+
+        for i in 0..log_len {
+            for (j, block) in coeffs.chunks_mut(1 << (i + 1)).enumerate() {
+                let twiddle = ntt.get_subspace_eval(round + i, j);
+                let (lo, hi) = block.split_at_mut(1 << i);
+                for (x0, x1) in lo.iter_mut().zip(hi.iter_mut()) {
+                    *x1 += *x0;
+                    *x0 += *x1 * twiddle;
+                }
+            }
+        }
+
+        coeffs.truncate(self.encoding.len() / RATE);
+        coeffs
+    }
+
+    //Decodes every coset on its own and returns the message only if all cosets agree. Agreeing cosets are exactly the words whose inverse transform over
+    //the full length has zero coefficients past encoding.len() / RATE, so this rejects anything that is not a codeword. The error names the first coset
+    //that disagrees with coset 0 and the base index of its first symbol that differs from the re-encoding of the message of coset 0.
+    #[instrument(skip_all, name = "decode checked", level = "debug")]
+    pub fn decode_checked<N>(&self, ntt: &N) -> Result<Vec<BinaryField128b>, CodeError>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let coset_len = self.encoding.len() / RATE;
+        let message = self.decode(ntt);
+
+        for (i, coset) in self.encoding.chunks(coset_len).enumerate().skip(1) {
+            let mut coset_message = coset.to_vec();
+            ntt.inverse_transform(&mut coset_message, i as u32, 0)?;
+            if coset_message == message {
+                continue;
+            }
+
+            let mut expected_coset = message.clone();
+            ntt.forward_transform(&mut expected_coset, i as u32, 0)?;
+            let bad_idx = coset
+                .iter()
+                .zip(expected_coset.iter())
+                .position(|(val, expected_val)| val != expected_val)
+                .expect("Cosets with different messages must differ");
+            return Err(CodeError::CosetMismatch {
+                coset: i,
+                base_idx: bad_idx,
+            });
+        }
+        Ok(message)
+    }
+
+    //Re-encodes the coefficients of the final polynomial, as sent when FRI stops at target_len, into the layer reached after round folds. This is the
+    //inverse of decode_at_round and lets the verifier check the query openings of the last folded layer against the terminal polynomial.
+    #[instrument(skip_all, name = "from final coeffs", level = "debug")]
+    pub fn from_final_coeffs<P, N>(
+        coeffs: &[BinaryField128b],
+        target_len: usize,
+        round: usize,
+        ntt: &N,
+    ) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            target_len.is_power_of_two() && target_len >= RATE,
+            "Target length {target_len} is not a power of 2 of at least {RATE}"
+        );
+        assert!(
+            coeffs.len() <= target_len / RATE,
+            "{} coefficients do not fit in a layer of length {target_len}",
+            coeffs.len()
+        );
+
+        let mut encoding = coeffs.to_vec();
+        encoding.resize(target_len, BinaryField128b::ZERO);
+        let log_len = target_len.trailing_zeros() as usize;
+
+        for i in (0..log_len).rev() {
+            for (j, block) in encoding.chunks_mut(1 << (i + 1)).enumerate() {
+                let twiddle = ntt.get_subspace_eval(round + i, j);
+                let (lo, hi) = block.split_at_mut(1 << i);
+                for (x0, x1) in lo.iter_mut().zip(hi.iter_mut()) {
+                    *x0 += *x1 * twiddle;
+                    *x1 += *x0;
+                }
+            }
+        }
+        Code { encoding }
+    }
+
+    //Reduces the RATE cosets to sum_c coeffs[c] * coset_c[i] for every index i of the base domain. The array type fixes the number of coefficients to RATE.
+    #[instrument(skip_all, name = "combine cosets", level = "debug")]
+    pub fn combine_cosets(&self, coeffs: &[BinaryField128b; RATE]) -> Vec<BinaryField128b> {
+        assert!(
+            self.encoding.len() % RATE == 0,
+            "Codeword of length {} does not split into {RATE} cosets",
+            self.encoding.len()
+        );
+
+        let matrix = self.as_matrix();
+        (0..matrix.coset_len())
+            .into_par_iter()
+            .map(|i| {
+                matrix
+                    .column(i)
+                    .zip(coeffs.iter())
+                    .map(|(val, coeff)| *coeff * val)
+                    .sum()
+            })
+            .collect()
+    }
+
+    //Borrows the codeword as the RATE by encoding.len() / RATE matrix whose row c is coset c, in the natural layout.
+    pub fn as_matrix(&self) -> MatrixView<'_> {
+        MatrixView {
+            encoding: &self.encoding,
+            coset_len: self.encoding.len() / RATE,
+        }
+    }
+
+    //self + blend * mask per symbol, masking a codeword for zero knowledge with a random codeword mask. The code is linear, so adding a codeword keeps
+    //a codeword a codeword and leaves the distance of any word to the code unchanged, the masked word is exactly as close to the code as self.
+    #[instrument(skip_all, name = "mask", level = "debug")]
+    pub fn mask(
+        &self,
+        mask: &Code<BinaryField128b>,
+        blend: BinaryField128b,
+    ) -> Code<BinaryField128b> {
+        assert_eq!(
+            self.encoding.len(),
+            mask.encoding.len(),
+            "Mask must have the length of the codeword"
+        );
+
+        let encoding = self
+            .encoding
+            .par_iter()
+            .zip(mask.encoding.par_iter())
+            .map(|(val, mask_val)| *val + blend * *mask_val)
+            .collect();
+        Code { encoding }
+    }
+
+    //Codeword of a uniformly random message of message_dim variables, to be used as a mask.
+    pub fn random_mask<N, R>(message_dim: usize, ntt: &N, rng: &mut R) -> Code<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b> + NttThreading,
+        R: Rng,
+    {
+        let message: Vec<BinaryField128b> = (0..1 << message_dim)
+            .map(|_| BinaryField128b::random(&mut *rng))
+            .collect();
+        Self::new(&message, ntt)
+    }
+
+    //Interleaves codewords of equal length so that position i holds the i-th symbol of every codeword, letting one Merkle leaf commit to all of them.
+    #[instrument(skip_all, name = "interleave", level = "debug")]
+    pub fn interleave(codes: &[Code<BinaryField128b>]) -> InterleavedCode {
+        assert!(!codes.is_empty(), "No codewords to interleave");
+
+        let len = codes[0].encoding.len();
+        assert!(
+            codes.iter().all(|code| code.encoding.len() == len),
+            "Codewords to interleave must have equal length"
+        );
+
+        let width = codes.len();
+        let mut encoding = vec![BinaryField128b::ZERO; len * width];
+        encoding
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(i, symbols)| {
+                for (symbol, code) in symbols.iter_mut().zip(codes.iter()) {
+                    *symbol = code.encoding[i];
+                }
+            });
+        InterleavedCode { encoding, width }
+    }
+
+    //Per-symbol product of two codewords of equal length, the evaluations of the product polynomial over the domain. The novel basis polynomial X_j
+    //has degree j, so for messages of degrees d1 and d2 this is a codeword of the same message dimension exactly when d1 + d2 < encoding.len() / RATE.
+    //Otherwise it is only the evaluation of the product on the domain, which has no reason to be close to the code.
+    #[instrument(skip_all, name = "hadamard", level = "debug")]
+    pub fn hadamard(&self, other: &Code<BinaryField128b>) -> Code<BinaryField128b> {
+        assert_eq!(
+            self.encoding.len(),
+            other.encoding.len(),
+            "Codewords to multiply must have equal length"
+        );
+
+        let encoding = self
+            .encoding
+            .par_iter()
+            .zip(other.encoding.par_iter())
+            .map(|(val, other_val)| *val * *other_val)
+            .collect();
+        Code { encoding }
+    }
+
+    //Symbol idx of Code::new(message, ntt) without encoding the rest of the codeword, in O(message.len()) multiplications.
+    pub fn symbol_at<F, N>(message: &[F], idx: usize, ntt: &N) -> BinaryField128b
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+        assert!(
+            idx < repacked_message.len() * RATE,
+            "Index {idx} out of range for a codeword of length {}",
+            repacked_message.len() * RATE
+        );
+        eval_repacked_at(&repacked_message, idx, ntt)
+    }
+
+    //symbol_at for every index, split across threads and repacking the message once. The cost is O(indices.len() * message.len()) multiplications divided
+    //over the threads, against O(message.len() * log(message.len())) for Code::new, so it pays off for query sets much smaller than log2 of the message.
+    #[instrument(skip_all, name = "symbols at", level = "debug")]
+    pub fn symbols_at<F, N>(message: &[F], indices: &[usize], ntt: &N) -> Vec<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let repacked_message = repack(message);
+        let len = repacked_message.len() * RATE;
+        assert!(
+            indices.iter().all(|idx| *idx < len),
+            "Index out of range for a codeword of length {len}"
+        );
+
+        indices
+            .par_iter()
+            .map(|idx| eval_repacked_at(&repacked_message, *idx, ntt))
+            .collect()
+    }
+
+    //Whether every symbol is zero, which is the case exactly for the codeword of the zero message. The parallel scan stops at the first nonzero symbol.
+    pub fn is_zero(&self) -> bool {
+        self.encoding
+            .par_iter()
+            .all(|val| *val == BinaryField128b::ZERO)
+    }
+
+    //The nonzero symbols of the codeword with their indices, see SparseCode.
+    pub fn to_sparse(&self) -> SparseCode {
+        let nonzeros = self
+            .encoding
+            .par_iter()
+            .enumerate()
+            .filter(|(_, val)| **val != BinaryField128b::ZERO)
+            .map(|(idx, val)| (idx, *val))
+            .collect();
+        SparseCode {
+            len: self.encoding.len(),
+            nonzeros,
+        }
+    }
+
+    //Every stride-th symbol of each coset, i.e. the evaluations over the domain points whose lowest log2(stride) bits are zero, the subspace spanned by
+    //the basis elements β_log2(stride) and up. The polynomial keeps its degree, so the result is a Reed-Solomon codeword of rate stride / RATE on that
+    //subspace, not a codeword of this crate's rate: decode, fold_code and is_valid_codeword assume the full domain and do not apply to it.
+    pub fn subsample(&self, stride: usize) -> Code<BinaryField128b> {
+        let coset_len = self.encoding.len() / RATE;
+        assert!(
+            stride.is_power_of_two() && stride <= coset_len,
+            "Stride {stride} is not a power of 2 dividing the coset length {coset_len}"
+        );
+
+        let encoding = self.encoding.iter().step_by(stride).copied().collect();
+        Code { encoding }
+    }
+
+    //sum_i self[i] * other[i], the sum of the symbols of self.hadamard(other). The domain is a subspace and the sum of a polynomial of degree below its size minus 1 over a
+    //subspace vanishes, so the inner product of two codewords of this crate's rate is always zero.
+    #[instrument(skip_all, name = "inner product", level = "debug")]
+    pub fn inner_product(&self, other: &Code<BinaryField128b>) -> BinaryField128b {
+        assert_eq!(
+            self.encoding.len(),
+            other.encoding.len(),
+            "Codewords must have equal length"
+        );
+
+        self.encoding
+            .par_iter()
+            .zip(other.encoding.par_iter())
+            .map(|(val, other_val)| *val * *other_val)
+            .sum()
+    }
+
+    //Computes sum_j coeffs[j] * codes[j] for codewords of equal length. By linearity of the encoding this is the codeword of the same combination of the messages.
+    #[instrument(skip_all, name = "linear combine", level = "debug")]
+    pub fn linear_combine(
+        codes: &[Code<BinaryField128b>],
+        coeffs: &[BinaryField128b],
+    ) -> Code<BinaryField128b> {
+        assert_eq!(
+            codes.len(),
+            coeffs.len(),
+            "Number of codewords and coefficients differ"
+        );
+        assert!(!codes.is_empty(), "No codewords to combine");
+
+        let len = codes[0].encoding.len();
+        assert!(
+            codes.iter().all(|code| code.encoding.len() == len),
+            "Codewords to combine must have equal length"
+        );
+
+        let encoding = (0..len)
+            .into_par_iter()
+            .map(|i| {
+                codes
+                    .iter()
+                    .zip(coeffs.iter())
+                    .map(|(code, coeff)| *coeff * code.encoding[i])
+                    .sum()
+            })
+            .collect();
+        Code { encoding }
+    }
+
+    //Point of the evaluation domain at codeword position idx. The NTT subspace is spanned by the canonical tower basis, forward_transform(_, i, 0)
+    //evaluates on the i-th coset of the message-sized subspace and Code::new appends the cosets in order, so the point is the field element whose bits are idx.
+    pub fn domain_point<P, N>(idx: usize, ntt: &N) -> BinaryField128b
+    where
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            idx < 1 << ntt.log_domain_size(),
+            "Index {idx} is outside the NTT domain"
+        );
+        BinaryField128b::new(idx as u128)
+    }
+
+    //domain_point of every position of the codeword, for bulk operations that index the domain instead of recomputing it per symbol. The vector is
+    //as large as the codeword itself, so it is only worth holding while such an operation runs.
+    #[instrument(skip_all, name = "all domain points", level = "debug")]
+    pub fn all_domain_points<P, N>(&self, ntt: &N) -> Vec<BinaryField128b>
+    where
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        (0..self.encoding.len())
+            .into_par_iter()
+            .map(|idx| Self::domain_point(idx, ntt))
+            .collect()
+    }
+
+    //Coefficients past encoding.len() / RATE of the inverse transform over the full codeword length. The full-length transform on coset 0 evaluates
+    //over the whole domain, so the syndrome is zero exactly for codewords and, by linearity, for a perturbed codeword it is the syndrome of the error alone.
+    #[instrument(skip_all, name = "syndrome", level = "debug")]
+    pub fn syndrome<N>(&self, ntt: &N) -> Vec<BinaryField128b>
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let mut coeffs = self.encoding.clone();
+        ntt.inverse_transform(&mut coeffs, 0, 0).unwrap();
+        coeffs.split_off(self.encoding.len() / RATE)
+    }
+
+    //Checks that every coset is the transform of the same message, i.e. that the encoding is a codeword of a message of length encoding.len() / RATE.
+    pub fn is_valid_codeword<N>(&self, ntt: &N) -> bool
+    where
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        let coset_len = self.encoding.len() / RATE;
+        let message = self.decode(ntt);
+
+        (1..RATE).all(|i| {
+            let mut temp = message.clone();
+            ntt.forward_transform(&mut temp, i as u32, 0).unwrap();
+            temp == self.encoding[i * coset_len..(i + 1) * coset_len]
+        })
+    }
+}
+
+//Bytes of every layer of a full fold of the codeword of a 2^message_dim symbol message at rate 1 / rate: the codeword and each folded layer down to
+//rate symbols, len + len / 2 + .. + rate = 2 * len - rate symbols of 16 bytes for len = rate * 2^message_dim.
+pub fn fold_tree_bytes(message_dim: usize, rate: usize) -> usize {
+    let len = rate << message_dim;
+    (2 * len - rate) * size_of::<BinaryField128b>()
+}
+
+//Packs the base field elements of message into BinaryField128b symbols, DEGREE elements at a time.
+pub(crate) fn repack<F>(message: &[F]) -> Vec<BinaryField128b>
+where
+    BinaryField128b: ExtensionField<F>,
+    F: BinaryField,
+{
+    message
+        .par_chunks(<BinaryField128b as ExtensionField<F>>::DEGREE)
+        .map(|base_elems| BinaryField128b::from_bases(base_elems).unwrap())
+        .collect()
+}
+
+//Zero-pads message to 1 << target_dim symbols so messages of different sizes can share one FRI instance. The padding only adds high variables,
+//so the padded message evaluated at (point, 0, ..., 0) equals the original message evaluated at point.
+pub fn pad_message(message: &[BinaryField128b], target_dim: usize) -> Vec<BinaryField128b> {
+    assert!(
+        message.len() <= 1 << target_dim,
+        "Message of length {} does not fit in dimension {target_dim}",
+        message.len()
+    );
+
+    let mut padded_message = Vec::with_capacity(1 << target_dim);
+    padded_message.extend_from_slice(message);
+    padded_message.resize(1 << target_dim, BinaryField128b::ZERO);
+    padded_message
+}
+
+//Symbols of a word over BinaryField1b packed 64 to a u64, symbol idx is bit idx % 64 of bits[idx / 64]. Produced by Code::pack_bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedBitCommitment {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl PackedBitCommitment {
+    pub fn unpack_bits(&self) -> Code<BinaryField128b> {
+        let encoding = (0..self.len)
+            .map(|idx| BinaryField128b::new(((self.bits[idx / 64] >> (idx % 64)) & 1) as u128))
+            .collect();
+        Code { encoding }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+//A layer saved part way through folding together with the absolute round of its next fold, so folding resumed from it indexes the twiddles by the
+//right round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldCheckpoint {
+    #[serde(with = "serde_code")]
+    pub code: Code<BinaryField128b>,
+    pub next_round: usize,
+}
+
+//The layer goes through serde as its symbols, rejecting the lengths from_bytes rejects.
+mod serde_code {
+    use binius_field::BinaryField128b;
+    use serde::{Deserializer, Serializer, de::Error};
+
+    use super::{Code, RATE};
+    use crate::prover::serde_symbols;
+
+    pub fn serialize<S: Serializer>(
+        code: &Code<BinaryField128b>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde_symbols::serialize(&code.encoding, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Code<BinaryField128b>, D::Error> {
+        let encoding = serde_symbols::deserialize(deserializer)?;
+        if !encoding.len().is_power_of_two() || encoding.len() < RATE {
+            return Err(D::Error::custom(format!(
+                "A layer of {} symbols is not a power of 2 of at least {RATE}",
+                encoding.len()
+            )));
+        }
+        Ok(Code { encoding })
+    }
+}
+
+impl FoldCheckpoint {
+    //Folds the rest of the way down to RATE symbols with the remaining challenges from next_round on, returning the same value as fold_all over all of
+    //the challenges on the original codeword.
+    pub fn resume<P, N>(&self, remaining_challenges: &[BinaryField128b], ntt: &N) -> BinaryField128b
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert_eq!(
+            self.code.encoding.len() >> remaining_challenges.len(),
+            RATE,
+            "Folding a layer of length {} to {RATE} symbols needs {} challenges",
+            self.code.encoding.len(),
+            self.code.encoding.len().trailing_zeros() as usize - LOG_RATE
+        );
+        self.code
+            .fold_rounds(remaining_challenges, self.next_round, ntt)
+            .idx(0)
+    }
+
+    //next_round as 8 little-endian bytes followed by Code::to_bytes of the layer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.next_round as u64).to_le_bytes().to_vec();
+        bytes.extend(self.code.to_bytes());
+        bytes
+    }
+
+    //Inverse of to_bytes. The layer must hold a power of 2 number of symbols, at least RATE.
+    pub fn from_bytes(bytes: &[u8]) -> Result<FoldCheckpoint, CodeError> {
+        let header = size_of::<u64>();
+        let symbols = bytes.len().saturating_sub(header) / size_of::<u128>();
+        if bytes.len() < header || !symbols.is_power_of_two() || symbols < RATE {
+            return Err(CodeError::UnexpectedLength {
+                expected: header + RATE * size_of::<u128>(),
+                actual: bytes.len(),
+            });
+        }
+
+        let (round_bytes, code_bytes) = bytes.split_at(header);
+        let next_round = u64::from_le_bytes(round_bytes.try_into().unwrap()) as usize;
+        let code = Code::from_bytes_expecting(
+            code_bytes,
+            symbols.trailing_zeros() as usize - LOG_RATE,
+            RATE,
+        )?;
+        Ok(FoldCheckpoint { code, next_round })
+    }
+}
+
+//Timing of one encode, as returned by Code::new_with_stats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EncodeStats {
+    pub elapsed: Duration,
+    pub symbols: usize,
+}
+
+impl EncodeStats {
+    pub fn symbols_per_sec(&self) -> f64 {
+        self.symbols as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+//Parameters of a codeword, as returned by Code::describe. rate is the inverse rate and num_cosets the number of message-sized cosets the codeword
+//is split into, equal while the code has a single fixed rate. num_fold_rounds is the number of folds down to RATE symbols, as in fold_all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeDescription {
+    pub message_dim: usize,
+    pub codeword_len: usize,
+    pub rate: usize,
+    pub num_cosets: usize,
+    pub footprint_bytes: usize,
+    pub num_fold_rounds: usize,
+}
+
+impl Display for CodeDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16}{}", "message_dim", self.message_dim)?;
+        writeln!(f, "{:<16}{}", "codeword_len", self.codeword_len)?;
+        writeln!(f, "{:<16}{}", "rate", self.rate)?;
+        writeln!(f, "{:<16}{}", "num_cosets", self.num_cosets)?;
+        writeln!(f, "{:<16}{}", "footprint_bytes", self.footprint_bytes)?;
+        write!(f, "{:<16}{}", "num_fold_rounds", self.num_fold_rounds)
+    }
+}
+
+//Scratch buffers reused across encodes of equal-length messages, so a hot loop of encodes does not reallocate on every call.
+#[derive(Clone, Debug, Default)]
+pub struct Encoder {
+    repacked_message: Vec<BinaryField128b>,
+    encoding: Vec<BinaryField128b>,
+}
+
+impl Encoder {
+    pub fn new() -> Encoder {
+        Encoder::default()
+    }
+
+    //Same output as Code::new, written into the internal buffer. The buffers are cleared, not freed, so equal-length encodes reuse their allocation.
+    #[instrument(skip_all, name = "encoder encode", level = "debug")]
+    pub fn encode<F, N>(&mut self, message: &[F], ntt: &N) -> &[BinaryField128b]
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+    {
+        self.repacked_message.clear();
+        self.repacked_message.par_extend(
+            message
+                .par_chunks(<BinaryField128b as ExtensionField<F>>::DEGREE)
+                .map(|base_elems| BinaryField128b::from_bases(base_elems).unwrap()),
+        );
+
+        let coset_len = self.repacked_message.len();
+        self.encoding.clear();
+        for i in 0..RATE {
+            self.encoding.extend_from_slice(&self.repacked_message);
+            ntt.forward_transform(&mut self.encoding[i * coset_len..], i as u32, 0)
+                .unwrap();
+        }
+        &self.encoding
+    }
+}
+
+//Pending encode started by Code::new_async.
+#[derive(Debug)]
+pub struct EncodeHandle {
+    receiver: Receiver<Code<BinaryField128b>>,
+}
+
+impl EncodeHandle {
+    //The codeword if the encode has finished, otherwise None without waiting. The codeword is handed out once, later polls return None.
+    pub fn try_poll(&self) -> Option<Code<BinaryField128b>> {
+        self.receiver.try_recv().ok()
+    }
+
+    //Blocks until the encode finishes. Called on a rayon worker, join keeps running the pool's pending jobs while it waits instead of parking the worker,
+    //since those jobs may be the encode's own and the worker the only one left to run them.
+    pub fn join(self) -> Code<BinaryField128b> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(code) => return code,
+                Err(TryRecvError::Disconnected) => panic!("encode task panicked"),
+                Err(TryRecvError::Empty) => {}
+            }
+            match rayon::yield_now() {
+                None => return self.receiver.recv().expect("encode task panicked"),
+                Some(Yield::Executed) => {}
+                Some(Yield::Idle) => match self.receiver.recv_timeout(Duration::from_millis(1)) {
+                    Ok(code) => return code,
+                    Err(RecvTimeoutError::Disconnected) => panic!("encode task panicked"),
+                    Err(RecvTimeoutError::Timeout) => {}
+                },
+            }
+        }
+    }
+}
+
+//Encodes messages over any level of the tower by lifting every element into BinaryField128b on its own, one symbol per element, rather than packing
+//DEGREE elements into a symbol as Code::new does. A protocol moving from BinaryField1b through BinaryField32b to BinaryField128b can hand each stage's
+//message straight to encode.
+#[derive(Clone, Copy, Debug)]
+pub struct TowerEncoder<'a, N> {
+    ntt: &'a N,
+}
+
+impl<'a, N> TowerEncoder<'a, N>
+where
+    N: AdditiveNTT<BinaryField128b>,
+{
+    pub fn new(ntt: &'a N) -> TowerEncoder<'a, N> {
+        TowerEncoder { ntt }
+    }
+
+    //Code::new of the message lifted element-wise into BinaryField128b, collected straight into the buffer that is encoded.
+    #[instrument(skip_all, name = "tower encode", level = "debug")]
+    pub fn encode<F>(&self, message: &[F]) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+    {
+        Code::new_from_iter(
+            message.iter().map(|elem| BinaryField128b::from(*elem)),
+            self.ntt,
+        )
+    }
+}
+
+//Codeword with its even and odd positions stored in separate contiguous halves, evens[i] = code[2i] and odds[i] = code[2i + 1]. The fold pair of i is then
+//evens[i] and odds[i], two aligned loads instead of a strided gather, which is the layout a packed butterfly kernel wants. fold keeps the layout, so a
+//chain of folds never goes back to the interleaved order until to_code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransposedCode {
+    pub evens: Vec<BinaryField128b>,
+    pub odds: Vec<BinaryField128b>,
+}
+
+impl TransposedCode {
+    pub fn from_code(code: &Code<BinaryField128b>) -> TransposedCode {
+        let (evens, odds) = code
+            .encoding
+            .par_chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .unzip();
+        TransposedCode { evens, odds }
+    }
+
+    pub fn to_code(&self) -> Code<BinaryField128b> {
+        let encoding = self
+            .evens
+            .iter()
+            .zip(self.odds.iter())
+            .flat_map(|(even, odd)| [*even, *odd])
+            .collect();
+        Code { encoding }
+    }
+
+    //Same result as Code::fold_code in the transposed layout, folded with the PackedBinaryField2x128b kernel of fold_packed.
+    pub fn fold<P, N>(&self, r: BinaryField128b, round: usize, ntt: &N) -> TransposedCode
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        self.fold_packed::<PackedBinaryField2x128b, P, N>(r, round, ntt)
+    }
+
+    //Folds PF::WIDTH pairs per butterfly: evens and odds are read as contiguous runs of PF::WIDTH symbols, the butterfly and the mix with r run on
+    //whole packed elements, and only the twiddles are looked up per symbol. Folded symbol i goes to evens or odds of the output by its parity, so each
+    //task folds 2 * PF::WIDTH consecutive pairs and splits the two packed results into one run of each. Layers shorter than that are folded per symbol.
+    #[instrument(skip_all, name = "fold transposed code", level = "debug")]
+    pub fn fold_packed<PF, P, N>(&self, r: BinaryField128b, round: usize, ntt: &N) -> TransposedCode
+    where
+        PF: PackedField<Scalar = BinaryField128b>,
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        assert!(
+            self.evens.len() >= 2,
+            "Cannot fold a transposed codeword of length {}",
+            2 * self.evens.len()
+        );
+
+        let half_len = self.evens.len() >> 1;
+        let mut evens = vec![BinaryField128b::ZERO; half_len];
+        let mut odds = vec![BinaryField128b::ZERO; half_len];
+
+        let width = PF::WIDTH;
+        if half_len < width {
+            for (k, (even, odd)) in evens.iter_mut().zip(odds.iter_mut()).enumerate() {
+                let (i0, i1) = (k << 1, (k << 1) | 1);
+                *even = fold(r, round, i0, self.evens[i0], self.odds[i0], ntt);
+                *odd = fold(r, round, i1, self.evens[i1], self.odds[i1], ntt);
+            }
+            return TransposedCode { evens, odds };
+        }
+
+        let packed_r = PF::broadcast(r);
+        evens
+            .par_chunks_exact_mut(width)
+            .zip(odds.par_chunks_exact_mut(width))
+            .enumerate()
+            .for_each(|(c, (even_out, odd_out))| {
+                let folded = [0, 1].map(|half| {
+                    let start = (2 * c + half) * width;
+                    let x0 = PF::from_scalars(self.evens[start..start + width].iter().copied());
+                    let x1 = PF::from_scalars(self.odds[start..start + width].iter().copied());
+                    let twiddles = PF::from_scalars(
+                        (start..start + width).map(|i| fold_twiddle(round, i, ntt)),
+                    );
+
+                    //The butterfly and (1 - r) * x0 + r * x1 of fold_with_twiddle, on PF::WIDTH pairs at once
+                    let x1 = x1 + x0;
+                    let x0 = x0 + x1 * twiddles;
+                    x0 + packed_r * (x0 + x1)
+                });
+                for (j, (even, odd)) in even_out.iter_mut().zip(odd_out.iter_mut()).enumerate() {
+                    let (i0, i1) = (j << 1, (j << 1) | 1);
+                    *even = folded[i0 / width].get(i0 % width);
+                    *odd = folded[i1 / width].get(i1 % width);
+                }
+            });
+        TransposedCode { evens, odds }
+    }
+}
+
+//One NTT of the largest size in use, shared by the encodes of every smaller message. The NTT's basis does not depend on its size, so the twiddles of
+//the subspace of a 2^m symbol coset are the leading entries of the tables of every larger domain, and a transform of 2^m symbols only reads those. An
+//encode through the context is therefore identical to Code::new with an NTT built for exactly that size.
+pub struct CodeContext {
+    ntt: MultithreadedNTT<BinaryField128b>,
+    max_message_dim: usize,
+}
+
+impl CodeContext {
+    //Precomputes the twiddles for messages of up to 2^max_message_dim BinaryField128b symbols.
+    pub fn new(max_message_dim: usize) -> Result<CodeContext, CodeError> {
+        let ntt =
+            SingleThreadedNTT::<BinaryField128b>::new(max_message_dim + LOG_RATE)?.multithreaded();
+        Ok(CodeContext {
+            ntt,
+            max_message_dim,
+        })
+    }
+
+    //There is no explicit sub-domain selection: the transforms of the repacked message are run on the shared NTT as they are, and since they only read
+    //the leading twiddles of each table, which are the twiddles of the message's own domain, the sub-domain is picked by the message length alone.
+    //The length is checked after repacking, which pads a partial last symbol, so the check sees exactly the size that is encoded.
+    #[instrument(skip_all, name = "context encode", level = "debug")]
+    pub fn encode<F>(&self, message: &[F]) -> Code<BinaryField128b>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+    {
+        let repacked_message = repack(message);
+        assert!(
+            repacked_message.len() <= 1 << self.max_message_dim,
+            "Message of {} symbols exceeds the context's maximum of 2^{}",
+            repacked_message.len(),
+            self.max_message_dim
+        );
+        Code::encode_repacked(&repacked_message, &self.ntt)
+    }
+
+    //The shared NTT, for folding or decoding the codewords encode returns.
+    pub fn ntt(&self) -> &MultithreadedNTT<BinaryField128b> {
+        &self.ntt
+    }
+}
+
+//Several codewords of equal length stored position-major: symbols i * width..(i + 1) * width are the i-th symbols of the width interleaved codewords.
+#[derive(Clone, Debug)]
+pub struct InterleavedCode {
+    pub encoding: Vec<BinaryField128b>,
+    pub width: usize,
+}
+
+impl InterleavedCode {
+    //Folds every lane independently under the shared challenge r. Lane j of the result is codes[j].fold_code(r, round, ntt).
+    #[instrument(skip_all, name = "fold interleaved code", level = "debug")]
+    pub fn fold_code<P, N>(&self, r: BinaryField128b, round: usize, ntt: &N) -> InterleavedCode
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let width = self.width;
+        let mut encoding = vec![BinaryField128b::ZERO; self.encoding.len() >> 1];
+
+        encoding
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(i, symbols)| {
+                let (symbols0, symbols1) =
+                    self.encoding[2 * i * width..2 * (i + 1) * width].split_at(width);
+                for (lane, symbol) in symbols.iter_mut().enumerate() {
+                    *symbol = fold(r, round, i, symbols0[lane], symbols1[lane], ntt);
+                }
+            });
+        InterleavedCode { encoding, width }
+    }
+
+    //The symbols at position idx, one per lane.
+    pub fn idx(&self, idx: usize) -> &[BinaryField128b] {
+        &self.encoding[idx * self.width..(idx + 1) * self.width]
+    }
+
+    //Extracts lane j as a standalone codeword.
+    pub fn lane(&self, j: usize) -> Code<BinaryField128b> {
+        assert!(
+            j < self.width,
+            "Lane {j} out of range for width {}",
+            self.width
+        );
+        Code {
+            encoding: self
+                .encoding
+                .iter()
+                .skip(j)
+                .step_by(self.width)
+                .copied()
+                .collect(),
+        }
+    }
+
+    //Number of positions, i.e. the length of each interleaved codeword.
+    pub fn len(&self) -> usize {
+        self.encoding.len() / self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.encoding.is_empty()
+    }
+}
+
+//Random access to the symbols of a layer, for folding without owning the codeword. An implementation may check each symbol as it is read, e.g.
+//against a Merkle commitment, and panic on a bad one.
+pub trait SymbolSource {
+    fn get(&self, idx: usize) -> BinaryField128b;
+}
+
+impl SymbolSource for Code<BinaryField128b> {
+    fn get(&self, idx: usize) -> BinaryField128b {
+        self.idx(idx)
+    }
+}
+
+//View of a codeword as a matrix with one row per coset, entry (c, j) is encoding[c * coset_len + j]. Columns hold the RATE symbols sharing a base index.
+#[derive(Clone, Copy, Debug)]
+pub struct MatrixView<'a> {
+    encoding: &'a [BinaryField128b],
+    coset_len: usize,
+}
+
+impl<'a> MatrixView<'a> {
+    pub fn get(&self, coset: usize, base_idx: usize) -> BinaryField128b {
+        assert!(
+            coset < RATE && base_idx < self.coset_len,
+            "Entry ({coset}, {base_idx}) outside a {RATE} by {} matrix",
+            self.coset_len
+        );
+        self.encoding[coset * self.coset_len + base_idx]
+    }
+
+    //Coset c.
+    pub fn row(&self, coset: usize) -> &'a [BinaryField128b] {
+        &self.encoding[coset * self.coset_len..(coset + 1) * self.coset_len]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [BinaryField128b]> + 'a {
+        self.encoding.chunks(self.coset_len)
+    }
+
+    //The symbol at base_idx of every coset, in coset order.
+    pub fn column(&self, base_idx: usize) -> impl Iterator<Item = BinaryField128b> + 'a {
+        self.encoding
+            .iter()
+            .skip(base_idx)
+            .step_by(self.coset_len)
+            .copied()
+    }
+
+    pub fn coset_len(&self) -> usize {
+        self.coset_len
+    }
+}
+
+//Some of the RATE cosets of a codeword, as produced by Code::new_cosets: symbols k * coset_len..(k + 1) * coset_len are coset cosets[k].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialCode {
+    pub encoding: Vec<BinaryField128b>,
+    pub cosets: Vec<u32>,
+}
+
+impl PartialCode {
+    pub fn coset_len(&self) -> usize {
+        self.encoding.len() / self.cosets.len()
+    }
+
+    //Symbols of coset c, the same as Code::coset(c, CosetOrder::Natural) of the full codeword. Panics if c was not encoded.
+    pub fn coset(&self, c: usize) -> &[BinaryField128b] {
+        let k = self
+            .cosets
+            .iter()
+            .position(|coset| *coset as usize == c)
+            .unwrap_or_else(|| panic!("Coset {c} was not encoded, only {:?}", self.cosets));
+        &self.encoding
+            [checked_coset_range(k, self.coset_len()).unwrap_or_else(|error| panic!("{error}"))]
+    }
+
+    //Symbol at position idx of the full codeword, the same as Code::idx(idx). Panics if the coset of idx was not encoded.
+    pub fn idx(&self, idx: usize) -> BinaryField128b {
+        let coset_len = self.coset_len();
+        self.coset(idx / coset_len)[idx % coset_len]
+    }
+}
+
+//Codeword of length len stored as its nonzero symbols, in increasing index order. A pair costs 24 bytes against 16 per dense symbol, so the sparse form
+//is smaller only while fewer than 2 / 3 of the symbols are nonzero. Encodings of random messages are almost never below that, this is for structured
+//words such as the encoding of a few low-index coefficients over a subfield domain, or differences of nearly equal codewords.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SparseCode {
+    pub len: usize,
+    pub nonzeros: Vec<(usize, BinaryField128b)>,
+}
+
+impl SparseCode {
+    pub fn to_dense(&self) -> Code<BinaryField128b> {
+        let mut encoding = vec![BinaryField128b::ZERO; self.len];
+        for (idx, val) in self.nonzeros.iter() {
+            encoding[*idx] = *val;
+        }
+        Code { encoding }
+    }
+
+    //Fraction of the symbols that are nonzero.
+    pub fn density(&self) -> f64 {
+        self.nonzeros.len() as f64 / self.len as f64
+    }
+}
+
+//Several codewords read as the one virtual word [codes[0] | codes[1] | ...], e.g. to commit to all of them under a single Merkle tree. The codes need not
+//share a length, offsets[k] is the global index of the first symbol of codes[k].
+#[derive(Clone, Debug)]
+pub struct ConcatCode {
+    pub codes: Vec<Code<BinaryField128b>>,
+    offsets: Vec<usize>,
+}
+
+impl ConcatCode {
+    pub fn new(codes: Vec<Code<BinaryField128b>>) -> ConcatCode {
+        let offsets = codes
+            .iter()
+            .scan(0, |offset, code| {
+                let start = *offset;
+                *offset += code.encoding.len();
+                Some(start)
+            })
+            .collect();
+        ConcatCode { codes, offsets }
+    }
+
+    //The symbol at global index idx, found in the last sub-code starting at or before idx.
+    pub fn idx(&self, idx: usize) -> BinaryField128b {
+        assert!(
+            idx < self.len(),
+            "Index {idx} out of range for length {}",
+            self.len()
+        );
+        let k = self.offsets.partition_point(|offset| *offset <= idx) - 1;
+        self.codes[k].idx(idx - self.offsets[k])
+    }
+
+    //All symbols in global index order, to be collected into the leaves of one Merkle tree.
+    pub fn symbols(&self) -> impl Iterator<Item = BinaryField128b> + '_ {
+        self.codes
+            .iter()
+            .flat_map(|code| code.encoding.iter().copied())
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.iter().map(|code| code.encoding.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+//Symbol idx of the codeword of the repacked message, sum_j message[j] * prod_{i in j} Ŝ_i(x) at the domain point x of idx. The product over the bits of
+//j is a tensor of (1, Ŝ_i(x)), so the sum folds one variable at a time in message.len() multiplications.
+fn eval_repacked_at<N>(message: &[BinaryField128b], idx: usize, ntt: &N) -> BinaryField128b
+where
+    N: AdditiveNTT<BinaryField128b>,
+{
+    let mut vals = message.to_vec();
+    for i in 0..message.len().trailing_zeros() as usize {
+        let s_i = match (idx >> i) & 1 {
+            1 => BinaryField128b::ONE + ntt.get_subspace_eval(i, idx >> (i + 1)),
+            _ => ntt.get_subspace_eval(i, idx >> (i + 1)),
+        };
+        let half_len = vals.len() >> 1;
+        for k in 0..half_len {
+            vals[k] = vals[k << 1] + s_i * vals[(k << 1) | 1];
+        }
+        vals.truncate(half_len);
+    }
+    vals[0]
+}
+
+const BATCH_INVERSE_CHUNK: usize = 1 << 10;
+
+//Inverts every element in place with Montgomery's trick: prefix products, one inversion of the total, then a backward pass peeling one factor off at a
+//time, about 3 multiplications per element. Panics if any element is zero, since the total is then zero too.
+pub fn batch_inverse(elems: &mut [BinaryField128b]) {
+    let mut prefix = Vec::with_capacity(elems.len());
+    let mut acc = BinaryField128b::ONE;
+    for elem in elems.iter() {
+        prefix.push(acc);
+        acc *= *elem;
+    }
+
+    let mut inv = acc
+        .invert()
+        .expect("batch_inverse of a slice containing zero");
+    for (elem, prefix) in elems.iter_mut().zip(prefix).rev() {
+        let elem_inv = inv * prefix;
+        inv *= *elem;
+        *elem = elem_inv;
+    }
+}
+
+//Ŝ_0(c), .., Ŝ_{vars - 1}(c) at an arbitrary field element c. Ŝ_0 is X and Ŝ_{i+1} = Ŝ_i(Ŝ_i + 1) / (Ŝ_i(β_{i+1})(Ŝ_i(β_{i+1}) + 1)), where
+//Ŝ_i(β_{i+1}) is the twiddle get_subspace_eval(i, 1).
+fn subspace_evals_at<N>(c: BinaryField128b, vars: usize, ntt: &N) -> Vec<BinaryField128b>
+where
+    N: AdditiveNTT<BinaryField128b>,
+{
+    let mut evals = Vec::with_capacity(vars);
+    let mut s_i = c;
+    for i in 0..vars {
+        evals.push(s_i);
+        if i + 1 < vars {
+            let beta_eval = ntt.get_subspace_eval(i, 1);
+            let norm = (beta_eval * (beta_eval + BinaryField128b::ONE))
+                .invert()
+                .unwrap();
+            s_i = s_i * (s_i + BinaryField128b::ONE) * norm;
+        }
+    }
+    evals
+}
+
+//Novel basis coefficients of f(x + c) from those of f, see Code::shift. Per variable i the coefficient of X_{k | 1 << i} also contributes Ŝ_i(c) times
+//itself to X_k.
+fn shift_novel_coeffs<N>(
+    coeffs: &[BinaryField128b],
+    c: BinaryField128b,
+    ntt: &N,
+) -> Vec<BinaryField128b>
+where
+    N: AdditiveNTT<BinaryField128b>,
+{
+    let vars = coeffs.len().trailing_zeros() as usize;
+    let mut shifted = coeffs.to_vec();
+    for (i, s_i) in subspace_evals_at(c, vars, ntt).into_iter().enumerate() {
+        shifted.par_chunks_mut(2 << i).for_each(|chunk| {
+            let (lo, hi) = chunk.split_at_mut(1 << i);
+            for (l, h) in lo.iter_mut().zip(hi.iter()) {
+                *l += s_i * *h;
+            }
+        });
+    }
+    shifted
+}
+
+//Novel basis polynomial X_j = prod Ŝ_i over the set bits i of j [LCH14], evaluated at the domain point of codeword position idx. This is symbol idx of
+//the codeword of the unit vector at j.
+pub fn novel_basis_eval<N>(j: usize, idx: usize, ntt: &N) -> BinaryField128b
+where
+    N: AdditiveNTT<BinaryField128b>,
+{
+    //Ŝ_i is linear with Ŝ_i(β_i) = 1 and vanishes on β_0..β_{i-1}, so at domain point idx it is bit i of idx plus the twiddle of the bits above i
+    (0..usize::BITS as usize - j.leading_zeros() as usize)
+        .filter(|i| (j >> i) & 1 == 1)
+        .map(|i| match (idx >> i) & 1 {
+            1 => BinaryField128b::ONE + ntt.get_subspace_eval(i, idx >> (i + 1)),
+            _ => ntt.get_subspace_eval(i, idx >> (i + 1)),
+        })
+        .product()
+}
+
+const SINGLE_THREADED_WARN_LEN: usize = 1 << 16;
+
+//Threading of an NTT, which AdditiveNTT does not expose. Code::new reads it to warn about large single-threaded encodes.
+pub trait NttThreading {
+    fn is_multithreaded(&self) -> bool;
+}
+
+impl<F: BinaryField> NttThreading for SingleThreadedNTT<F> {
+    fn is_multithreaded(&self) -> bool {
+        false
+    }
+}
+
+impl<F: BinaryField> NttThreading for MultithreadedNTT<F> {
+    fn is_multithreaded(&self) -> bool {
+        true
+    }
+}
+
+impl<F: BinaryField> NttThreading for DynamicDispatchNTT<F> {
+    fn is_multithreaded(&self) -> bool {
+        matches!(
+            self,
+            DynamicDispatchNTT::MultiThreaded(_) | DynamicDispatchNTT::MultiThreadedPrecompute(_)
+        )
+    }
+}
+
+impl<T: NttThreading> NttThreading for &T {
+    fn is_multithreaded(&self) -> bool {
+        (**self).is_multithreaded()
+    }
+}
+
+//Positions (2 * idx, 2 * idx + 1) of the pair fold combines into idx. Vec lengths are bounded by isize::MAX, so this only fails for an idx that cannot
+//come from a real codeword, e.g. one computed from an untrusted length.
+pub fn checked_pair_indices(idx: usize) -> Result<(usize, usize), CodeError> {
+    match idx.checked_mul(2) {
+        Some(even) => Ok((even, even | 1)),
+        None => Err(CodeError::IndexOverflow {
+            index: idx,
+            scale: 2,
+        }),
+    }
+}
+
+//Positions slot * coset_len..(slot + 1) * coset_len of the coset in slot.
+pub fn checked_coset_range(slot: usize, coset_len: usize) -> Result<Range<usize>, CodeError> {
+    match (slot + 1).checked_mul(coset_len) {
+        Some(end) => Ok(end - coset_len..end),
+        None => Err(CodeError::IndexOverflow {
+            index: slot + 1,
+            scale: coset_len,
+        }),
+    }
+}
+
+//Fold of FRI-Binius [DP24] (https://eprint.iacr.org/2024/504, Section 4). The inverse additive NTT butterfly turns the pair of evaluations over a fibre
+//into the even and odd parts of the fibre polynomial, which are then combined as (1 - r) * even + r * odd. Chained over all rounds this evaluates the
+//message as a multilinear polynomial at the challenges, which is what ties the fold to the sum check.
+//idx is the parent position: the output index in the folded layer, whose children are (val0, val1) at positions 2 * idx and 2 * idx + 1 of the layer
+//being folded. fold_code passes i for the pair (2i, 2i + 1), and a verifier replaying query q of the unfolded layer passes q >> 1, never q.
+#[inline(always)]
+pub fn fold<P, N>(
+    r: BinaryField128b,
+    round: usize,
+    idx: usize,
+    val0: BinaryField128b,
+    val1: BinaryField128b,
+    ntt: &N,
+) -> BinaryField128b
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    //twiddle for inverse ntt component of the fold i.e the twiddle for the butterfly unit
+    //at the index if we were to apply the inverse ntt
+
+    fold_with_twiddle(r, fold_twiddle(round, idx, ntt), val0, val1)
+}
+
+//fold_code of the codeword chunks[0] | chunks[1] | .., for codewords held in several buffers, e.g. mmapped files. Chunks may have any length, including
+//odd and empty ones: a pair split by a boundary is folded from the last symbol of one chunk and the first of the next. Pairs inside a chunk are folded
+//in parallel, so chunks of even length at even offsets cost the same as one contiguous codeword.
+#[instrument(skip_all, name = "fold chunks", level = "debug")]
+pub fn fold_chunks<P, N>(
+    chunks: &[&[BinaryField128b]],
+    r: BinaryField128b,
+    round: usize,
+    ntt: &N,
+) -> Vec<BinaryField128b>
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    let len: usize = chunks.iter().map(|chunk| chunk.len()).sum();
+    assert!(len % 2 == 0, "Cannot fold a codeword of odd length {len}");
+
+    let mut folded = Vec::with_capacity(len / 2);
+    let mut carry = None;
+    for chunk in chunks {
+        let mut chunk = *chunk;
+        if let (Some(left), Some(right)) = (carry, chunk.first()) {
+            folded.push(fold(r, round, folded.len(), left, *right, ntt));
+            chunk = &chunk[1..];
+            carry = None;
+        }
+
+        let start = folded.len();
+        let pairs_len = chunk.len() & !1;
+        folded.par_extend(
+            chunk[..pairs_len]
+                .par_chunks_exact(2)
+                .enumerate()
+                .map(|(i, pair)| fold(r, round, start + i, pair[0], pair[1], ntt)),
+        );
+        if chunk.len() > pairs_len {
+            carry = Some(chunk[pairs_len]);
+        }
+    }
+    folded
+}
+
+//fold_code of a codeword serialized as by Code::to_bytes, each symbol the 16 byte little-endian representation of its u128 value, with the output
+//in the same format. Symbols are read and written pair by pair, so only the input bytes and the half-size output are ever held.
+#[instrument(skip_all, name = "fold bytes", level = "debug")]
+pub fn fold_bytes<P, N>(bytes: &[u8], r: BinaryField128b, round: usize, ntt: &N) -> Vec<u8>
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    let symbol_bytes = size_of::<u128>();
+    assert!(
+        bytes.len() % (2 * symbol_bytes) == 0,
+        "{} bytes are not a whole number of symbol pairs",
+        bytes.len()
+    );
+
+    let read_symbol =
+        |symbol: &[u8]| BinaryField128b::new(u128::from_le_bytes(symbol.try_into().unwrap()));
+    bytes
+        .par_chunks_exact(2 * symbol_bytes)
+        .enumerate()
+        .flat_map_iter(|(i, pair)| {
+            let (left, right) = pair.split_at(symbol_bytes);
+            fold(r, round, i, read_symbol(left), read_symbol(right), ntt)
+                .val()
+                .to_le_bytes()
+        })
+        .collect()
+}
+
+//Fold of the single pair feeding parent_idx in the next layer, left and right being the symbols at 2 * parent_idx and 2 * parent_idx + 1. This is fold
+//under the name of its indexing convention, for verifiers replaying one query.
+#[inline(always)]
+pub fn fold_one<P, N>(
+    r: BinaryField128b,
+    round: usize,
+    parent_idx: usize,
+    left: BinaryField128b,
+    right: BinaryField128b,
+    ntt: &N,
+) -> BinaryField128b
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    fold(r, round, parent_idx, left, right, ntt)
+}
+
+//fold with a challenge r in a subfield S of BinaryField128b, so r * (x0 + x1) is a subfield by extension multiply instead of a full 128 bit one.
+//Returns fold(BinaryField128b::from(r), ..). A challenge from S is drawn from 2^S::N_BITS values rather than 2^128, and the soundness error of each
+//round grows to about the number of bad challenges over |S|, so S must be large enough for the target security, e.g. BinaryField32b gives at most 32
+//bits per round.
+#[inline(always)]
+pub fn fold_subfield<S, P, N>(
+    r: S,
+    round: usize,
+    idx: usize,
+    val0: BinaryField128b,
+    val1: BinaryField128b,
+    ntt: &N,
+) -> BinaryField128b
+where
+    BinaryField128b: ExtensionField<S> + ExtensionField<P>,
+    S: BinaryField,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    let twiddle = fold_twiddle(round, idx, ntt);
+    let (mut x0, mut x1) = (val0, val1);
+    x1 += x0;
+    x0 += x1 * twiddle;
+
+    if r == S::ZERO {
+        x0
+    } else if r == S::ONE {
+        x1
+    } else {
+        x0 + (x0 + x1) * r
+    }
+}
+
+//fold with the twiddle read from schedule.
+#[inline(always)]
+pub fn fold_scheduled(
+    r: BinaryField128b,
+    round: usize,
+    idx: usize,
+    val0: BinaryField128b,
+    val1: BinaryField128b,
+    schedule: &FoldSchedule,
+) -> BinaryField128b {
+    fold_with_twiddle(r, schedule.twiddle(round, idx), val0, val1)
+}
+
+//fold with its butterfly twiddle already looked up, shared by fold and fold_scheduled.
+#[inline(always)]
+fn fold_with_twiddle(
+    r: BinaryField128b,
+    twiddle: BinaryField128b,
+    val0: BinaryField128b,
+    val1: BinaryField128b,
+) -> BinaryField128b {
+    let (mut x0, mut x1) = (val0, val1);
+    x1 += x0;
+    x0 += x1 * twiddle;
+
+    //r is the same for every pair of a round, so these branches are perfectly predicted and add no cost for random challenges
+    if r == BinaryField128b::ZERO {
+        x0
+    } else if r == BinaryField128b::ONE {
+        x1
+    } else {
+        x0 + r * (x0 + x1)
+    }
+}
+
+//Twiddle of the butterfly that fold applies to the pair (2 * idx, 2 * idx + 1) at round, i.e. the normalised subspace polynomial of round evaluated at the
+//domain point of position 2 * idx << round. At round 0 this is the domain point 2 * idx itself.
+#[inline(always)]
+pub fn fold_twiddle<P, N>(round: usize, idx: usize, ntt: &N) -> BinaryField128b
+where
+    BinaryField128b: ExtensionField<P>,
+    P: BinaryField,
+    N: AdditiveNTT<P>,
+{
+    //Round round has 2^(log_domain - round - 1) pairs, shrinking by half per round while round grows by one
+    debug_assert!(
+        round < ntt.log_domain_size() && idx >> (ntt.log_domain_size() - round - 1) == 0,
+        "Pair {idx} is outside round {round} of a domain of size 2^{}",
+        ntt.log_domain_size()
+    );
+    BinaryField128b::from(ntt.get_subspace_eval(round, idx))
+}
+
+//fold_twiddle of every pair of the first num_rounds rounds over the full NTT domain, computed once and shared by the prover's fold_code_cached and the
+//verifier's verify_fold_step so both fold with the same twiddles. Round k has half as many pairs as round k - 1, so the rounds are stored back to back
+//and round k starts at len - (len >> k) for len the domain size, fewer than len symbols in total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldSchedule {
+    twiddles: Vec<BinaryField128b>,
+    log_len: usize,
+    num_rounds: usize,
+}
+
+impl FoldSchedule {
+    #[instrument(skip_all, name = "fold schedule", level = "debug")]
+    pub fn new<P, N>(ntt: &N, num_rounds: usize) -> FoldSchedule
+    where
+        BinaryField128b: ExtensionField<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        let log_len = ntt.log_domain_size();
+        assert!(
+            num_rounds <= log_len,
+            "Cannot fold a domain of size 2^{log_len} {num_rounds} times"
+        );
+
+        let twiddles = (0..num_rounds)
+            .flat_map(|round| (0..1 << (log_len - round - 1)).map(move |idx| (round, idx)))
+            .collect::<Vec<(usize, usize)>>()
+            .into_par_iter()
+            .map(|(round, idx)| fold_twiddle(round, idx, ntt))
+            .collect();
+
+        FoldSchedule {
+            twiddles,
+            log_len,
+            num_rounds,
+        }
+    }
+
+    //The twiddle fold_twiddle(round, idx, ntt) of the NTT the schedule was built from.
+    pub fn twiddle(&self, round: usize, idx: usize) -> BinaryField128b {
+        assert!(
+            round < self.num_rounds,
+            "Round {round} is past the {} scheduled rounds",
+            self.num_rounds
+        );
+        assert!(
+            idx < 1 << (self.log_len - round - 1),
+            "Pair {idx} is outside round {round}"
+        );
+        self.twiddles[(1 << self.log_len) - (1 << (self.log_len - round)) + idx]
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.num_rounds
+    }
+}
+
+//Fold with the challenge applied to the raw pair before any butterfly: (1 - r) * val0 + r * val1, the line through the two evaluations taken at r.
+//No external FRI specification was found that defines the fold this way. The evaluation-form fold of FRI [BBHR18] (https://eccc.weizmann.ac.il/report/2017/134)
+//interpolates over the fibre with its domain points, which is what the butterfly of fold does, so it matches fold and not fold_pre. The combination itself is
+//the fold of a multilinear evaluation table in one variable as a sum-check prover does it, f(r, x') = (1 - r) * f(0, x') + r * f(1, x').
+//It is not the [DP24] fold: fold applies the same combination to the butterflied pair, i.e. fold(r, val0, val1) == fold_pre(r, x0, x1) with (x0, x1)
+//the inverse NTT butterfly of (val0, val1), so only fold produces the multilinear evaluation of the message.
+#[inline(always)]
+pub fn fold_pre(
+    r: BinaryField128b,
+    val0: BinaryField128b,
+    val1: BinaryField128b,
+) -> BinaryField128b {
+    val0 + r * (val0 + val1)
+}
+
+//Random message of 2^l symbols and a multithreaded NTT sized for its codeword, the fixture most tests start from.
+#[cfg(test)]
+pub(crate) fn random_poly_and_ntt(
+    l: usize,
+) -> (Vec<BinaryField128b>, MultithreadedNTT<BinaryField128b>) {
+    let poly = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+    (poly, ntt)
+}
+
+#[test]
+fn test_fold() {
+    let l = 11;
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .into_par_iter()
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + 2)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new(&poly, &ntt);
+
+    let r: Vec<BinaryField128b> = (0..l)
+        .into_iter()
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let r_eq = LagrangeBases::gen_from_point(&r);
+
+    let poly_eval: BinaryField128b = poly
+        .par_iter()
+        .zip(r_eq.vals)
+        .map(|(coeff, eq_val)| *coeff * eq_val)
+        .sum();
+
+    let mut folded_code = code.fold_code(r[0], 0, &ntt);
+    for round in 1..l {
+        folded_code = folded_code.fold_code(r[round], round, &ntt)
+    }
+
+    println!("{:?}", poly_eval);
+    println!("{:?}", folded_code)
+}
+
+#[test]
+fn test_ntt() {
+    let l = 11;
+    let mut poly: Vec<BinaryField64b> = (0..1 << l)
+        .into_par_iter()
+        .map(|_| BinaryField64b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField32b>::new(13)
+        .unwrap()
+        .multithreaded();
+
+    let res = ntt.forward_transform_ext(&mut poly, 0);
+    match res {
+        Ok(()) => println!("ok"),
+        Err(error) => println!("{:?}", error),
+    }
+}
+
+#[test]
+fn test_quotient() {
+    let l = 6;
+    let mut poly = vec![BinaryField128b::ZERO; 1 << l];
+    poly[0] = BinaryField128b::random(thread_rng());
+    poly[1] = BinaryField128b::random(thread_rng());
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new(&poly, &ntt);
+
+    //The first novel basis polynomial is X itself, so f(z) = poly[0] + poly[1] * z and the quotient is the constant poly[1].
+    let z = BinaryField128b::random(thread_rng());
+    let quotient = code.quotient(z, poly[0] + poly[1] * z, &ntt);
+
+    assert!(quotient.is_valid_codeword(&ntt));
+    assert!(quotient.encoding.iter().all(|val| *val == poly[1]));
+}
+
+#[test]
+fn test_batch_inverse() {
+    let elems: Vec<BinaryField128b> = (0..1000)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let mut inverses = elems.clone();
+    batch_inverse(&mut inverses);
+    for (elem, inverse) in elems.iter().zip(inverses.iter()) {
+        assert_eq!(*inverse, elem.invert().unwrap());
+    }
+
+    let mut empty: Vec<BinaryField128b> = vec![];
+    batch_inverse(&mut empty);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_fold_to_size() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+
+    let r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let final_value = code.fold_all(&r, &ntt);
+    let folded_code = code.fold_to_size(&r, RATE, &ntt);
+    assert!(folded_code.encoding.iter().all(|val| *val == final_value));
+
+    let partially_folded_code = code.fold_to_size(&r[..3], 1 << (l + LOG_RATE - 3), &ntt);
+    assert_eq!(
+        partially_folded_code.encoding.len(),
+        1 << (l + LOG_RATE - 3)
+    );
+}
+
+#[test]
+fn test_message_dim() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(10 + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    for l in [3, 10] {
+        let poly = vec![BinaryField128b::ONE; 1 << l];
+        let code = Code::new(&poly, &ntt);
+
+        assert_eq!(code.message_dim(), l);
+        assert_eq!(code.rate(), RATE);
+        assert_eq!(code.encoding.len(), code.rate() << code.message_dim());
+    }
+}
+
+#[test]
+#[should_panic(expected = "shorter than")]
+fn test_message_dim_short() {
+    let code = Code {
+        encoding: vec![BinaryField128b::ONE; RATE / 2],
+    };
+    code.message_dim();
+}
+
+#[test]
+fn test_new_from_iter() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let iter_code = Code::new_from_iter(poly.into_iter(), &ntt);
+
+    assert_eq!(code.encoding, iter_code.encoding);
+}
+
+#[test]
+fn test_try_new() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    assert_eq!(Code::try_new(&poly, &ntt).unwrap(), Code::new(&poly, &ntt));
+
+    let long_poly: Vec<BinaryField128b> = (0..2 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    match Code::try_new(&long_poly, &ntt) {
+        Err(CodeError::MessageTooLong {
+            message_symbols,
+            max_symbols,
+        }) => assert_eq!((message_symbols, max_symbols), (2 << l, 1 << l)),
+        other => panic!("expected MessageTooLong, got {other:?}"),
+    }
+
+    //Subfield messages are counted in packed symbols, 2^(l + 2) 32 bit elements fill every symbol of the domain
+    let bits: Vec<BinaryField32b> = (0..1 << (l + 2))
+        .map(|_| BinaryField32b::random(thread_rng()))
+        .collect();
+    assert!(Code::try_new(&bits, &ntt).is_ok());
+    let bits: Vec<BinaryField32b> = (0..(1 << (l + 2)) + 1)
+        .map(|_| BinaryField32b::random(thread_rng()))
+        .collect();
+    match Code::try_new(&bits, &ntt) {
+        Err(CodeError::MessageTooLong {
+            message_symbols,
+            max_symbols,
+        }) => assert_eq!((message_symbols, max_symbols), ((1 << l) + 1, 1 << l)),
+        other => panic!("expected MessageTooLong, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_is_multithreaded() {
+    fn through_ref<N: NttThreading>(ntt: &N) -> bool {
+        NttThreading::is_multithreaded(&ntt)
+    }
+
+    let ntt = || SingleThreadedNTT::<BinaryField128b>::new(8).unwrap();
+    assert!(!ntt().is_multithreaded());
+    assert!(ntt().multithreaded().is_multithreaded());
+    assert!(through_ref(&ntt().multithreaded()));
+
+    assert!(!DynamicDispatchNTT::SingleThreaded(ntt()).is_multithreaded());
+    assert!(DynamicDispatchNTT::MultiThreaded(ntt().multithreaded()).is_multithreaded());
+}
+
+#[test]
+fn test_domain_point() {
+    let l = 6;
+    let mut poly = vec![BinaryField128b::ZERO; 1 << l];
+    poly[0] = BinaryField128b::random(thread_rng());
+    poly[1] = BinaryField128b::random(thread_rng());
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new(&poly, &ntt);
+
+    for i in 0..code.encoding.len() {
+        assert_eq!(
+            code.idx(i),
+            poly[0] + poly[1] * Code::domain_point(i, &ntt),
+            "Codeword does not match the polynomial at index {i}"
+        );
+    }
+}
+
+#[test]
+fn test_encoder() {
+    let l = 8;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let mut encoder = Encoder::new();
+    for _ in 0..4 {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+
+        assert_eq!(encoder.encode(&poly, &ntt), Code::new(&poly, &ntt).encoding);
+    }
+}
+
+#[test]
+fn test_checksum() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    assert_eq!(code.checksum(), BinaryField128b::ZERO);
+
+    for coset in code.encoding.chunks(1 << l) {
+        assert_eq!(
+            coset.iter().copied().sum::<BinaryField128b>(),
+            poly[(1 << l) - 1]
+        );
+    }
+
+    let delta = BinaryField128b::random(thread_rng());
+    code.encoding[17] += delta;
+    assert_eq!(code.checksum(), delta);
+}
+
+#[test]
+fn test_fold_pre() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(10)
+        .unwrap()
+        .multithreaded();
+
+    for round in 0..4 {
+        for idx in 0..8 {
+            let r = BinaryField128b::random(thread_rng());
+            let (val0, val1) = (
+                BinaryField128b::random(thread_rng()),
+                BinaryField128b::random(thread_rng()),
+            );
+
+            let twiddle = ntt.get_subspace_eval(round, idx);
+            let x1 = val0 + val1;
+            let x0 = val0 + x1 * twiddle;
+
+            assert_eq!(fold(r, round, idx, val0, val1, &ntt), fold_pre(r, x0, x1));
+            assert_ne!(
+                fold(r, round, idx, val0, val1, &ntt),
+                fold_pre(r, val0, val1)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pad_message() {
+    let (l, target_dim) = (5, 8);
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(target_dim + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let padded_code = Code::new(&pad_message(&poly, target_dim), &ntt);
+
+    let mut r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let eval = LagrangeBases::gen_from_point(&r).evaluate(&poly);
+
+    r.resize(target_dim, BinaryField128b::ZERO);
+    assert_eq!(padded_code.fold_all(&r, &ntt), eval);
+}
+
+#[test]
+fn test_single_threaded_ntt() {
+    let l = 8;
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let single_threaded_ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap();
+    let multithreaded_ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new(&poly, &single_threaded_ntt);
+    assert_eq!(code.encoding, Code::new(&poly, &multithreaded_ntt).encoding);
+    assert_eq!(
+        code.fold_all(&r, &single_threaded_ntt),
+        code.fold_all(&r, &multithreaded_ntt)
+    );
+    assert_eq!(code.decode(&single_threaded_ntt), poly);
+}
+
+#[test]
+fn test_fold_code_with_eq() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let mut eq = LagrangeBases::gen_from_point(&r);
+    let eval = eq.evaluate(&poly);
+
+    let mut folded_code = Code::new(&poly, &ntt);
+    assert_eq!(folded_code.decode_at_round(0, &ntt), poly);
+
+    for round in 0..l {
+        folded_code = folded_code.fold_code_with_eq(r[round], round, &ntt, &mut eq);
+        assert_eq!(
+            eq.evaluate(&folded_code.decode_at_round(round + 1, &ntt)),
+            eval,
+            "Invariant broken after round {round}"
+        );
+    }
+    assert_eq!(folded_code.idx(0), eval);
+}
+
+#[test]
+fn test_new_with_offset() {
+    let l = 6;
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE + 1)
+        .unwrap()
+        .multithreaded();
+
+    assert_eq!(
+        Code::new_with_offset(&poly, 0, &ntt).unwrap().encoding,
+        Code::new(&poly, &ntt).encoding
+    );
+
+    let code = Code::new_with_offset(&poly, RATE, &ntt).unwrap();
+    for (i, coset) in code.encoding.chunks(1 << l).enumerate() {
+        let mut temp = poly.clone();
+        ntt.forward_transform(&mut temp, (RATE + i) as u32, 0)
+            .unwrap();
+        assert_eq!(coset, temp);
+    }
+
+    match Code::new_with_offset(&poly, RATE + 1, &ntt) {
+        Err(CodeError::OffsetOutOfRange { offset, max_offset }) => {
+            assert_eq!((offset, max_offset), (RATE + 1, RATE))
+        }
+        _ => panic!("Oversized offset was accepted"),
+    }
+    assert!(matches!(
+        Code::new_with_offset(&poly, usize::MAX, &ntt),
+        Err(CodeError::OffsetOutOfRange {
+            offset: usize::MAX,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_from_final_coeffs() {
+    let (l, rounds) = (8, 5);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let r: Vec<BinaryField128b> = (0..rounds)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let target_len = 1 << (l + LOG_RATE - rounds);
+    let last_layer = Code::new(&poly, &ntt).fold_to_size(&r, target_len, &ntt);
+    let final_coeffs = last_layer.decode_at_round(rounds, &ntt);
+
+    assert_eq!(
+        Code::from_final_coeffs(&final_coeffs, target_len, rounds, &ntt).encoding,
+        last_layer.encoding
+    );
+    assert_eq!(
+        Code::from_final_coeffs(&poly, 1 << (l + LOG_RATE), 0, &ntt).encoding,
+        Code::new(&poly, &ntt).encoding
+    );
+}
+
+#[test]
+fn test_combine_cosets() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let mut coeffs = [BinaryField128b::ZERO; RATE];
+    coeffs[0] = BinaryField128b::ONE;
+    assert_eq!(code.combine_cosets(&coeffs), code.encoding[..1 << l]);
+
+    let coeffs: [BinaryField128b; RATE] =
+        std::array::from_fn(|_| BinaryField128b::random(thread_rng()));
+    let combined = code.combine_cosets(&coeffs);
+    for (i, val) in combined.iter().enumerate() {
+        let expected: BinaryField128b = (0..RATE)
+            .map(|c| coeffs[c] * code.idx(c * (1 << l) + i))
+            .sum();
+        assert_eq!(*val, expected);
+    }
+}
+
+#[test]
+fn test_new_auto() {
+    use binius_field::PackedBinaryField1x128b;
+
+    fn check<F>(l: usize)
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+    {
+        let poly: Vec<F> = (0..1 << l).map(|_| F::random(thread_rng())).collect();
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+            .unwrap()
+            .multithreaded();
+
+        //Every kernel new_auto can dispatch to, whichever this build selects
+        let code = Code::new(&poly, &ntt);
+        for encoding in [
+            Code::new_packed::<_, PackedBinaryField1x128b, _>(&poly, &ntt).encoding,
+            Code::new_packed::<_, PackedBinaryField2x128b, _>(&poly, &ntt).encoding,
+            Code::new_packed::<_, PackedBinaryField4x128b, _>(&poly, &ntt).encoding,
+            Code::new_auto(&poly, &ntt).encoding,
+        ] {
+            assert_eq!(encoding, code.encoding);
+        }
+    }
+
+    //Messages of one and two symbols are shorter than the packed widths and take the scalar fallback
+    for l in [0, 1, 3, 8] {
+        check::<BinaryField128b>(l);
+    }
+    check::<BinaryField32b>(8);
+}
+
+#[test]
+fn test_interleaved_fold() {
+    let (l, width) = (6, 3);
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let codes: Vec<Code<BinaryField128b>> = (0..width)
+        .map(|_| {
+            let poly: Vec<BinaryField128b> = (0..1 << l)
+                .map(|_| BinaryField128b::random(thread_rng()))
+                .collect();
+            Code::new(&poly, &ntt)
+        })
+        .collect();
+
+    let interleaved_code = Code::interleave(&codes);
+    assert_eq!(interleaved_code.len(), codes[0].encoding.len());
+
+    let r = BinaryField128b::random(thread_rng());
+    let folded_interleaved_code = interleaved_code.fold_code(r, 0, &ntt);
+    for (j, code) in codes.iter().enumerate() {
+        assert_eq!(interleaved_code.lane(j).encoding, code.encoding);
+        assert_eq!(
+            folded_interleaved_code.lane(j).encoding,
+            code.fold_code(r, 0, &ntt).encoding
+        );
+    }
+}
+
+#[test]
+fn test_decode_checked() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    assert_eq!(code.decode_checked(&ntt).unwrap(), poly);
+
+    code.encoding[(2 << l) + 5] += BinaryField128b::ONE;
+    code.encoding[(3 << l) + 1] += BinaryField128b::ONE;
+    match code.decode_checked(&ntt) {
+        Err(CodeError::CosetMismatch { coset, base_idx }) => {
+            assert_eq!((coset, base_idx), (2, 5))
+        }
+        _ => panic!("Corrupted codeword was decoded"),
+    }
+}
+
+#[test]
+fn test_fold_code_ext() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let r = BinaryField32b::random(thread_rng());
+    assert_eq!(
+        code.fold_code_ext(r, 0, &ntt).encoding,
+        code.fold_code(BinaryField128b::from(r), 0, &ntt).encoding
+    );
+}
+
+#[test]
+fn test_try_new_ext() {
+    use binius_field::BinaryField8b;
+
+    let l = 8;
+    let poly: Vec<BinaryField64b> = (0..1 << l)
+        .map(|_| BinaryField64b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField32b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    assert_eq!(
+        Code::try_new_ext(&poly, &ntt).unwrap().encoding,
+        Code::new_ext(&poly, &ntt).encoding
+    );
+
+    match Code::try_new_ext(&poly[..(1 << l) - 1], &ntt) {
+        Err(CodeError::UnalignedMessage {
+            message_len,
+            degree,
+        }) => assert_eq!((message_len, degree), ((1 << l) - 1, 2)),
+        _ => panic!("Message not packing into BinaryField128b was accepted"),
+    }
+
+    let small_ntt = SingleThreadedNTT::<BinaryField32b>::new(l - 2)
+        .unwrap()
+        .multithreaded();
+    assert!(matches!(
+        Code::try_new_ext(&poly, &small_ntt),
+        Err(CodeError::MessageTooLong {
+            message_symbols: 128,
+            max_symbols: 16
+        })
+    ));
+
+    //The codeword needs 2^9 points, more than an NTT over BinaryField8b can ever have, whatever its domain size
+    let byte_ntt = SingleThreadedNTT::<BinaryField8b>::new(6)
+        .unwrap()
+        .multithreaded();
+    assert!(matches!(
+        Code::try_new_ext(&poly, &byte_ntt),
+        Err(CodeError::FieldMismatch {
+            log_domain: 9,
+            field_bits: 8
+        })
+    ));
+}
+
+#[test]
+fn test_new_ext_matches_new() {
+    let l = 8;
+    let poly: Vec<BinaryField64b> = (0..1 << l)
+        .map(|_| BinaryField64b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField32b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+    let wide_ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    //The twiddles of the BinaryField32b NTT embed into BinaryField128b, so transforming the packed limbs is the same as transforming the packed symbols
+    let code = Code::new_ext(&poly, &ntt);
+    assert_eq!(code.encoding.len(), RATE << (l - 1));
+    assert_eq!(code, Code::new(&repack(&poly), &wide_ntt));
+    assert!(code.is_valid_codeword(&wide_ntt));
+}
+
+#[test]
+fn test_syndrome() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    let syndrome = code.syndrome(&ntt);
+    assert_eq!(syndrome.len(), (RATE - 1) << l);
+    assert!(syndrome.iter().all(|coeff| *coeff == BinaryField128b::ZERO));
+
+    let (bad_idx, delta) = (77, BinaryField128b::random(thread_rng()));
+    code.encoding[bad_idx] += delta;
+    let mut error = Code {
+        encoding: vec![BinaryField128b::ZERO; code.encoding.len()],
+    };
+    error.encoding[bad_idx] = delta;
+
+    let syndrome = code.syndrome(&ntt);
+    assert!(syndrome.iter().any(|coeff| *coeff != BinaryField128b::ZERO));
+    assert_eq!(syndrome, error.syndrome(&ntt));
+}
+
+//Loads the (message, codeword) vectors pinning the encoding convention: coset order, novel basis and twiddle indexing. Symbols are stored as the hex
+//value of their u128 representation.
+#[cfg(test)]
+fn load_test_vectors() -> Vec<(Vec<BinaryField128b>, Vec<BinaryField128b>)> {
+    let file: serde_json::Value =
+        serde_json::from_str(include_str!("../../test_vectors/encode.json"))
+            .expect("failed to parse test vectors");
+    assert_eq!(file["rate"], RATE, "Test vectors are for a different rate");
+
+    let parse_symbols = |symbols: &serde_json::Value| -> Vec<BinaryField128b> {
+        symbols
+            .as_array()
+            .expect("symbols must be an array")
+            .iter()
+            .map(|symbol| {
+                let hex = symbol.as_str().expect("symbol must be a hex string");
+                BinaryField128b::new(u128::from_str_radix(hex, 16).expect("invalid hex symbol"))
+            })
+            .collect()
+    };
+
+    file["vectors"]
+        .as_array()
+        .expect("vectors must be an array")
+        .iter()
+        .map(|vector| {
+            let message = parse_symbols(&vector["message"]);
+            assert_eq!(
+                message.len() as u64,
+                1 << vector["log_len"].as_u64().unwrap()
+            );
+            (message, parse_symbols(&vector["codeword"]))
+        })
+        .collect()
+}
+
+#[test]
+fn test_encode_vectors() {
+    let vectors = load_test_vectors();
+    assert!(!vectors.is_empty());
+
+    for (message, codeword) in vectors {
+        let l = message.len().trailing_zeros() as usize;
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+            .unwrap()
+            .multithreaded();
+
+        assert_eq!(
+            Code::new(&message, &ntt).encoding,
+            codeword,
+            "Encoding of the message of length 2^{l} changed"
+        );
+    }
+}
+
+//Reference encoder that shares nothing with the NTT: symbol idx is the message polynomial evaluated at the domain point whose bits are idx, i.e.
+//sum_k bit_k(idx) * β_k with β_k = 2^k the canonical basis, enumerated in natural order so coset c holds the points c * 2^dim + 0..2^dim. Each Ŝ_i is
+//built from its definition, prod over u < 2^i of (x - u) normalised to 1 at β_i, which is quadratic in the domain size and only meant for tiny messages.
+#[cfg(test)]
+fn encode_by_evaluation(message: &[BinaryField128b]) -> Vec<BinaryField128b> {
+    let message_dim = message.len().trailing_zeros() as usize;
+    let subspace_poly = |i: usize, x: BinaryField128b| -> BinaryField128b {
+        (0..1u128 << i)
+            .map(|u| x - BinaryField128b::new(u))
+            .product()
+    };
+    let norms: Vec<BinaryField128b> = (0..message_dim)
+        .map(|i| {
+            subspace_poly(i, BinaryField128b::new(1 << i))
+                .invert()
+                .unwrap()
+        })
+        .collect();
+
+    (0..message.len() * RATE)
+        .map(|idx| {
+            let x = BinaryField128b::new(idx as u128);
+            let s_hat: Vec<BinaryField128b> = (0..message_dim)
+                .map(|i| subspace_poly(i, x) * norms[i])
+                .collect();
+            message
+                .iter()
+                .enumerate()
+                .map(|(j, coeff)| {
+                    (0..message_dim)
+                        .filter(|i| (j >> i) & 1 == 1)
+                        .map(|i| s_hat[i])
+                        .product::<BinaryField128b>()
+                        * *coeff
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[test]
+fn test_encode_by_evaluation() {
+    for l in 1..5 {
+        let (poly, ntt) = random_poly_and_ntt(l);
+
+        let reference = encode_by_evaluation(&poly);
+        let code = Code::new(&poly, &ntt);
+        for (idx, symbol) in reference.iter().enumerate() {
+            assert_eq!(
+                code.idx(idx),
+                *symbol,
+                "Symbol {idx} of a message of length 2^{l}"
+            );
+        }
+
+        //A permuted layout is still a codeword but no longer matches position by position
+        let bit_reversed = Code::new_with_order(&poly, CosetOrder::BitReversed, &ntt);
+        assert_ne!(bit_reversed.encoding, reference);
+    }
+}
+
+#[test]
+fn test_fold_rounds() {
+    let (l, k) = (8, 3);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    let partial_layer = code.fold_rounds(&r[..k], 0, &ntt);
+    assert_eq!(partial_layer.encoding.len(), 1 << (l + LOG_RATE - k));
+
+    let folded_code = partial_layer.fold_rounds(&r[k..], k, &ntt);
+    assert_eq!(folded_code.idx(0), code.fold_all(&r, &ntt));
+}
+
+#[test]
+fn test_from_bytes_expecting() {
+    let l = 5;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + 1 + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let code = Code::new(&poly, &ntt);
+
+    let decoded_code = Code::from_bytes_expecting(&code.to_bytes(), l, RATE).unwrap();
+    assert_eq!(decoded_code.encoding, code.encoding);
+
+    //A codeword of a larger message must not pass as the expected one.
+    let substituted_poly: Vec<BinaryField128b> = (0..1 << (l + 1))
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let substituted_code = Code::new(&substituted_poly, &ntt);
+    match Code::from_bytes_expecting(&substituted_code.to_bytes(), l, RATE) {
+        Err(CodeError::UnexpectedLength { expected, actual }) => {
+            assert_eq!(
+                (expected, actual),
+                ((16 * RATE) << l, (16 * RATE) << (l + 1))
+            )
+        }
+        _ => panic!("Substituted codeword was accepted"),
+    }
+    assert!(Code::from_bytes_expecting(&code.to_bytes()[1..], l, RATE).is_err());
+}
+
+#[test]
+fn test_even_odd() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let (even, odd) = code.even_odd();
+    assert_eq!(even.len(), code.encoding.len() / 2);
+
+    let interleaved: Vec<BinaryField128b> = even
+        .iter()
+        .zip(odd.iter())
+        .flat_map(|(val0, val1)| [*val0, *val1])
+        .collect();
+    assert_eq!(interleaved, code.encoding);
+}
+
+#[test]
+fn test_new_and_fold() {
+    let l = 12;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let r0 = BinaryField128b::random(thread_rng());
+
+    assert_eq!(
+        Code::new_and_fold(&poly, r0, &ntt).encoding,
+        Code::new(&poly, &ntt).fold_code(r0, 0, &ntt).encoding
+    );
+}
+
+#[test]
+fn test_deep_fold() {
+    use crate::utils::mle::multilinear_eval;
+
+    //The layer a 2^30 codeword reaches after 20 folds, folded down to RATE symbols in round 28 without building the earlier layers
+    let log_domain = 30;
+    let (start_round, dim) = (20, 8);
+    let message: Vec<BinaryField128b> = (0..1 << dim)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let challenges: Vec<BinaryField128b> = (0..dim)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(log_domain)
+        .unwrap()
+        .multithreaded();
+
+    let layer = Code::from_final_coeffs(&message, RATE << dim, start_round, &ntt);
+    assert_eq!(
+        start_round + layer.encoding.len().trailing_zeros() as usize,
+        log_domain
+    );
+
+    //Any mis-scaled twiddle index on the way down breaks the multilinear evaluation
+    let folded = layer.fold_rounds(&challenges, start_round, &ntt);
+    assert_eq!(
+        folded.encoding,
+        vec![multilinear_eval(&message, &challenges); RATE]
+    );
+
+    //Every intermediate layer is a codeword over the domain of the round it was folded into
+    let mut layer = layer;
+    for (i, r) in challenges.iter().enumerate() {
+        layer = layer.fold_code(*r, start_round + i, &ntt);
+        let coeffs = layer.decode_at_round(start_round + i + 1, &ntt);
+        assert!(
+            coeffs[layer.encoding.len() / RATE..]
+                .iter()
+                .all(|coeff| *coeff == BinaryField128b::ZERO)
+        );
+    }
+    assert_eq!(layer, folded);
+
+    //An empty codeword has no pairs to read twiddles for
+    let empty = Code { encoding: vec![] };
+    assert!(
+        empty
+            .fold_code(challenges[0], start_round, &ntt)
+            .encoding
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_fold_twiddle() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(8)
+        .unwrap()
+        .multithreaded();
+
+    //The first normalised subspace polynomial is X, the second X(X + 1) / (β_1(β_1 + 1)) with β_1 = 2 the second basis element.
+    let beta_1 = BinaryField128b::new(2);
+    let norm = (beta_1 * (beta_1 + BinaryField128b::ONE)).invert().unwrap();
+    for idx in 0..8 {
+        assert_eq!(
+            fold_twiddle(0, idx, &ntt),
+            BinaryField128b::new(2 * idx as u128)
+        );
+
+        let x = BinaryField128b::new((idx as u128) << 2);
+        assert_eq!(
+            fold_twiddle(1, idx, &ntt),
+            x * (x + BinaryField128b::ONE) * norm
+        );
+    }
+}
+
+#[test]
+fn test_coset_order() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(
+        Code::new_with_order(&poly, CosetOrder::Natural, &ntt).encoding,
+        code.encoding
+    );
+
+    let reversed_code = Code::new_with_order(&poly, CosetOrder::BitReversed, &ntt);
+    let natural_cosets: Vec<&[BinaryField128b]> = code.encoding.chunks(1 << l).collect();
+    let reversed_cosets: Vec<&[BinaryField128b]> = reversed_code.encoding.chunks(1 << l).collect();
+    assert_eq!(
+        reversed_cosets,
+        [
+            natural_cosets[0],
+            natural_cosets[2],
+            natural_cosets[1],
+            natural_cosets[3]
+        ]
+    );
+
+    for c in 0..RATE {
+        assert_eq!(
+            reversed_code.coset(c, CosetOrder::BitReversed),
+            code.coset(c, CosetOrder::Natural)
+        );
+    }
+    for i in 0..code.encoding.len() {
+        assert_eq!(
+            reversed_code.idx_in_order(i, CosetOrder::BitReversed),
+            code.idx(i)
+        );
+    }
+}
+
+#[test]
+fn test_fold_fast_paths() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(10)
+        .unwrap()
+        .multithreaded();
+
+    for round in 0..4 {
+        for idx in 0..8 {
+            let (val0, val1) = (
+                BinaryField128b::random(thread_rng()),
+                BinaryField128b::random(thread_rng()),
+            );
+
+            let twiddle = fold_twiddle(round, idx, &ntt);
+            let x1 = val0 + val1;
+            let x0 = val0 + x1 * twiddle;
+
+            for r in [BinaryField128b::ZERO, BinaryField128b::ONE] {
+                assert_eq!(fold(r, round, idx, val0, val1, &ntt), x0 + r * (x0 + x1));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_eq_up_to_coset_perm() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let reversed_code = Code::new_with_order(&poly, CosetOrder::BitReversed, &ntt);
+    assert_ne!(code, reversed_code);
+    assert!(code.eq_up_to_coset_perm(&reversed_code));
+    assert!(reversed_code.eq_up_to_coset_perm(&code));
+
+    let mut other_code = code.clone();
+    other_code.encoding[..1 << l].copy_from_slice(&reversed_code.encoding[(1 << l)..(2 << l)]);
+    assert!(!code.eq_up_to_coset_perm(&other_code));
+
+    let empty_code = Code { encoding: vec![] };
+    assert!(empty_code.eq_up_to_coset_perm(&empty_code));
+
+    let ragged_code = Code {
+        encoding: code.encoding[..RATE + 1].to_vec(),
+    };
+    assert!(!ragged_code.eq_up_to_coset_perm(&ragged_code));
+}
+
+#[test]
+fn test_fold_code_per_round_ntt() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    let mut folded_code = code.clone();
+    for (round, r) in r.iter().enumerate() {
+        //A fresh, larger NTT every round. It shares the canonical basis, so its twiddles of round k agree with those of ntt.
+        let round_ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE + round).unwrap();
+        folded_code = folded_code.fold_code(*r, round, &round_ntt);
+    }
+
+    assert_eq!(folded_code, code.fold_to_size(&r, RATE, &ntt));
+}
+
+#[test]
+fn test_fold_all_checked() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut r: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    r[2] = BinaryField128b::ZERO;
+
+    let code = Code::new(&poly, &ntt);
+    assert!(matches!(
+        code.fold_all_checked(&r, true, &ntt),
+        Err(CodeError::ZeroChallenge { round: 2 })
+    ));
+    assert_eq!(
+        code.fold_all_checked(&r, false, &ntt).unwrap(),
+        code.fold_all(&r, &ntt)
+    );
+}
+
+#[test]
+fn test_hadamard() {
+    let l = 6;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    //Both messages have degree below 2^(l - 1), so their product has degree below 2^l.
+    let low_degree_poly = || -> Vec<BinaryField128b> {
+        (0..1 << l)
+            .map(|i| match i < 1 << (l - 1) {
+                true => BinaryField128b::random(thread_rng()),
+                false => BinaryField128b::ZERO,
+            })
+            .collect()
+    };
+    let (code, other_code) = (
+        Code::new(&low_degree_poly(), &ntt),
+        Code::new(&low_degree_poly(), &ntt),
+    );
+    assert!(code.hadamard(&other_code).is_valid_codeword(&ntt));
+
+    let full_degree_poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let full_degree_code = Code::new(&full_degree_poly, &ntt);
+    assert!(
+        !full_degree_code
+            .hadamard(&full_degree_code)
+            .is_valid_codeword(&ntt)
+    );
+}
+
+#[test]
+fn test_restrict() {
+    let (l, drop_vars) = (8, 3);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+
+    let (zero_code, one_code) = (
+        code.fold_code(BinaryField128b::ZERO, 0, &ntt),
+        code.fold_code(BinaryField128b::ONE, 0, &ntt),
+    );
+    let summed: Vec<BinaryField128b> = zero_code
+        .encoding
+        .iter()
+        .zip(one_code.encoding.iter())
+        .map(|(x0, x1)| *x0 + *x1)
+        .collect();
+    assert_eq!(code.restrict(1, &ntt).encoding, summed);
+
+    let summed_poly: Vec<BinaryField128b> = poly
+        .chunks(1 << drop_vars)
+        .map(|block| block.iter().copied().sum())
+        .collect();
+    assert_eq!(
+        code.restrict(drop_vars, &ntt)
+            .decode_at_round(drop_vars, &ntt),
+        summed_poly
+    );
+}
+
+#[test]
+fn test_fold_sum() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let layer_sum =
+        |code: &Code<BinaryField128b>| code.encoding.iter().copied().sum::<BinaryField128b>();
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in 0..3 {
+        let difference = layer_sum(&code.fold_code(BinaryField128b::ONE, round, &ntt))
+            - layer_sum(&code.fold_code(BinaryField128b::ZERO, round, &ntt));
+        assert_eq!(code.fold_sum(round, &ntt), difference);
+
+        code = code.fold_code(BinaryField128b::random(thread_rng()), round, &ntt);
+    }
+}
+
+#[test]
+fn test_update_symbol() {
+    let l = 6;
+    let (mut poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for _ in 0..8 {
+        let message_idx = thread_rng().gen_range(0..1 << l);
+        let new_val = BinaryField128b::random(thread_rng());
+
+        code.update_symbol(message_idx, poly[message_idx], new_val, &ntt);
+        poly[message_idx] = new_val;
+    }
+
+    assert_eq!(code, Code::new(&poly, &ntt));
+}
+
+#[test]
+fn test_all_domain_points() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let domain_points = code.all_domain_points(&ntt);
+
+    assert_eq!(domain_points.len(), code.encoding.len());
+    for (i, point) in domain_points.iter().enumerate() {
+        assert_eq!(*point, Code::domain_point(i, &ntt));
+    }
+}
+
+#[test]
+fn test_concat_code() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(8 + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let codes: Vec<Code<BinaryField128b>> = [4, 8, 6]
+        .into_iter()
+        .map(|l| {
+            let poly: Vec<BinaryField128b> = (0..1 << l)
+                .map(|_| BinaryField128b::random(thread_rng()))
+                .collect();
+            Code::new(&poly, &ntt)
+        })
+        .collect();
+
+    let concat_code = ConcatCode::new(codes.clone());
+    assert_eq!(concat_code.len(), (16 + 256 + 64) * RATE);
+
+    let mut global = 0;
+    for code in codes.iter() {
+        for i in 0..code.encoding.len() {
+            assert_eq!(concat_code.idx(global), code.idx(i));
+            global += 1;
+        }
+    }
+    assert!(
+        concat_code
+            .symbols()
+            .eq(codes.iter().flat_map(|code| code.encoding.clone()))
+    );
+}
+
+#[test]
+fn test_fold_schedule() {
+    let l = 7;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let schedule = FoldSchedule::new(&ntt, l);
+    for round in 0..l {
+        for idx in 0..1 << (l + LOG_RATE - round - 1) {
+            assert_eq!(
+                schedule.twiddle(round, idx),
+                ntt.get_subspace_eval(round, idx)
+            );
+        }
+    }
+
+    let (mut code, mut cached_code) = (Code::new(&poly, &ntt), Code::new(&poly, &ntt));
+    for round in 0..l {
+        let r = BinaryField128b::random(thread_rng());
+        code = code.fold_code(r, round, &ntt);
+        cached_code = cached_code.fold_code_cached(r, round, &schedule);
+        assert_eq!(code, cached_code);
+    }
+}
+
+#[test]
+fn test_new_cosets() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let partial_code = Code::new_cosets(&poly, &[0, 2], &ntt);
+
+    assert_eq!(partial_code.encoding.len(), 2 << l);
+    for c in [0, 2] {
+        assert_eq!(partial_code.coset(c), code.coset(c, CosetOrder::Natural));
+    }
+    for idx in (0..1 << l).chain(2 << l..3 << l) {
+        assert_eq!(partial_code.idx(idx), code.idx(idx));
+    }
+}
+
+#[test]
+fn test_checked_indices() {
+    assert_eq!(checked_pair_indices(5).unwrap(), (10, 11));
+    assert!(matches!(
+        checked_pair_indices(usize::MAX / 2 + 1),
+        Err(CodeError::IndexOverflow { scale: 2, .. })
+    ));
+
+    assert_eq!(checked_coset_range(2, 64).unwrap(), 128..192);
+    assert!(matches!(
+        checked_coset_range(RATE - 1, usize::MAX / 2),
+        Err(CodeError::IndexOverflow { index: RATE, .. })
+    ));
+}
+
+#[test]
+fn test_as_matrix() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let matrix = code.as_matrix();
+    let len = matrix.coset_len();
+
+    for c in 0..RATE {
+        assert_eq!(matrix.row(c), code.coset(c, CosetOrder::Natural));
+        for j in 0..len {
+            assert_eq!(matrix.get(c, j), code.encoding[c * len + j]);
+        }
+    }
+    assert_eq!(matrix.rows().count(), RATE);
+    for j in 0..len {
+        assert!(
+            matrix
+                .column(j)
+                .eq((0..RATE).map(|c| code.encoding[c * len + j]))
+        );
+    }
+}
+
+#[test]
+fn test_fold_code_from() {
+    let l = 7;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in 0..3 {
+        let r = BinaryField128b::random(thread_rng());
+        let folded_code = Code::fold_code_from(&code, code.encoding.len(), r, round, &ntt);
+
+        assert_eq!(folded_code, code.fold_code(r, round, &ntt));
+        code = folded_code;
+    }
+}
+
+#[test]
+fn test_multilinear_eval() {
+    use crate::utils::mle::multilinear_eval;
+
+    let l = 12;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let point: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    assert_eq!(
+        multilinear_eval(&poly, &point),
+        Code::new(&poly, &ntt).fold_all(&point, &ntt)
+    );
+    assert_eq!(
+        multilinear_eval(&poly, &point),
+        LagrangeBases::gen_from_point(&point).evaluate(&poly)
+    );
+}
+
+#[test]
+fn test_self_check() {
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let challenges: Vec<BinaryField128b> = (0..4)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let mut layers = vec![Code::new(&poly, &ntt)];
+    for (round, r) in challenges.iter().enumerate() {
+        let folded_layer = layers[round].fold_code(*r, round, &ntt);
+        layers.push(folded_layer);
+    }
+    assert!(Code::self_check(&layers, &challenges, 16, &ntt).is_ok());
+
+    for symbol in layers[2].encoding.iter_mut() {
+        *symbol += BinaryField128b::ONE;
+    }
+    assert!(matches!(
+        Code::self_check(&layers, &challenges, 16, &ntt),
+        Err(CodeError::InconsistentFold { round: 1, .. })
+    ));
+}
+
+#[test]
+fn test_new_novel_basis() {
+    let l = 5;
+    let coeffs: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new_novel_basis(&coeffs, &ntt);
+    assert_eq!(code, Code::new(&coeffs, &ntt));
+
+    for idx in 0..code.encoding.len() {
+        let eval: BinaryField128b = coeffs
+            .iter()
+            .enumerate()
+            .map(|(j, coeff)| *coeff * novel_basis_eval(j, idx, &ntt))
+            .sum();
+        assert_eq!(code.idx(idx), eval);
+    }
+}
+
+#[test]
+fn test_new_with_stats() {
+    let l = 12;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let (code, stats) = Code::new_with_stats(&poly, &ntt);
+    assert_eq!(code, Code::new(&poly, &ntt));
+    assert_eq!(stats.symbols, RATE << l);
+    assert!(stats.elapsed > Duration::ZERO);
+    assert!(stats.symbols_per_sec().is_finite() && stats.symbols_per_sec() > 0.0);
+}
+
+#[test]
+fn test_pack_bits() {
+    let bit_code = Code {
+        encoding: (0..200)
+            .map(|_| BinaryField128b::new(thread_rng().gen_range(0..2)))
+            .collect(),
+    };
+
+    let packed = bit_code.pack_bits().unwrap();
+    assert_eq!(packed.len(), 200);
+    assert_eq!(packed.unpack_bits(), bit_code);
+
+    let mut code = bit_code.clone();
+    code.encoding[77] = BinaryField128b::new(2);
+    assert!(matches!(
+        code.pack_bits(),
+        Err(CodeError::NotInSubfield { idx: 77 })
+    ));
+}
+
+#[test]
+fn test_verify_subfield() {
+    let l = 6;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let mut bits: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::new(thread_rng().gen_range(0..2)))
+        .collect();
+
+    let code = Code::new(&bits, &ntt);
+    assert!(code.verify_subfield::<BinaryField1b, _>(&ntt));
+    assert!(code.verify_subfield::<BinaryField32b, _>(&ntt));
+    assert!(code.verify_subfield::<BinaryField128b, _>(&ntt));
+
+    bits[37] = BinaryField128b::random(thread_rng());
+    let code = Code::new(&bits, &ntt);
+    assert!(!code.verify_subfield::<BinaryField1b, _>(&ntt));
+    assert!(!code.verify_subfield::<BinaryField32b, _>(&ntt));
+    assert!(code.verify_subfield::<BinaryField128b, _>(&ntt));
+
+    //A word off the code fails even when its first coset decodes into the subfield
+    let mut word = Code::new(&vec![BinaryField128b::ZERO; 1 << l], &ntt);
+    word.encoding[(1 << l) + 3] = BinaryField128b::ONE;
+    assert!(!word.verify_subfield::<BinaryField1b, _>(&ntt));
+}
+
+#[test]
+fn test_fold_subfield() {
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(10)
+        .unwrap()
+        .multithreaded();
+
+    for round in 0..4 {
+        for idx in 0..8 {
+            let (val0, val1) = (
+                BinaryField128b::random(thread_rng()),
+                BinaryField128b::random(thread_rng()),
+            );
+
+            for r in [
+                BinaryField32b::ZERO,
+                BinaryField32b::ONE,
+                BinaryField32b::random(thread_rng()),
+            ] {
+                assert_eq!(
+                    fold_subfield(r, round, idx, val0, val1, &ntt),
+                    fold(BinaryField128b::from(r), round, idx, val0, val1, &ntt)
+                );
+            }
+            let r = BinaryField64b::random(thread_rng());
+            assert_eq!(
+                fold_subfield(r, round, idx, val0, val1, &ntt),
+                fold(BinaryField128b::from(r), round, idx, val0, val1, &ntt)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_new_sparse() {
+    let l = 8;
+    let nonzeros: Vec<(usize, BinaryField128b)> = [0, 3, 64, 200, 255]
+        .into_iter()
+        .map(|idx| (idx, BinaryField128b::random(thread_rng())))
+        .collect();
+
+    let mut poly = vec![BinaryField128b::ZERO; 1 << l];
+    for (idx, val) in nonzeros.iter() {
+        poly[*idx] = *val;
+    }
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    assert_eq!(
+        Code::new_sparse(&nonzeros, 1 << l, &ntt),
+        Code::new(&poly, &ntt)
+    );
+}
+
+#[test]
+fn test_fold_checkpoint() {
+    let (l, split) = (8, 3);
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let challenges: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    let checkpoint = FoldCheckpoint {
+        code: code.fold_rounds(&challenges[..split], 0, &ntt),
+        next_round: split,
+    };
+
+    let restored = FoldCheckpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+    assert_eq!(restored, checkpoint);
+    assert_eq!(
+        restored.resume(&challenges[split..], &ntt),
+        code.fold_all(&challenges, &ntt)
+    );
+
+    let mut truncated = checkpoint.to_bytes();
+    truncated.truncate(truncated.len() - 16);
+    assert!(FoldCheckpoint::from_bytes(&truncated).is_err());
+
+    let json = serde_json::to_string(&checkpoint).unwrap();
+    let restored: FoldCheckpoint = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, checkpoint);
+    assert_eq!(
+        restored.resume(&challenges[split..], &ntt),
+        code.fold_all(&challenges, &ntt)
+    );
+
+    let ragged = serde_json::json!({ "code": [1, 2, 3], "next_round": split });
+    assert!(serde_json::from_value::<FoldCheckpoint>(ragged).is_err());
+}
+
+#[test]
+fn test_mask() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let mask = Code::random_mask(l, &ntt, &mut thread_rng());
+    let blend = BinaryField128b::random(thread_rng());
+
+    let masked_code = code.mask(&mask, blend);
+    assert!(masked_code.is_valid_codeword(&ntt));
+    assert_ne!(masked_code, code);
+    assert_eq!(masked_code.mask(&mask, -blend), code);
+}
+
+#[test]
+fn test_encodable_with() {
+    //Generic over the message and NTT fields with no bound beyond EncodableWith
+    fn encode_generic<F, P, N>(message: &[F], ntt: &N) -> Code<BinaryField128b>
+    where
+        F: EncodableWith<P>,
+        P: BinaryField,
+        N: AdditiveNTT<P>,
+    {
+        Code::new_ext(message, ntt)
+    }
+
+    let l = 8;
+    let poly: Vec<BinaryField64b> = (0..1 << l)
+        .map(|_| BinaryField64b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField32b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    assert_eq!(
+        encode_generic(&poly, &ntt),
+        Code::try_new_ext(&poly, &ntt).unwrap()
+    );
+}
+
+#[test]
+fn test_inner_product() {
+    let l = 6;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let random_code = || {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        Code::new(&poly, &ntt)
+    };
+    let (mut code, other_code) = (random_code(), random_code());
+
+    assert_eq!(code.inner_product(&other_code), BinaryField128b::ZERO);
+
+    //Perturbing symbol j by delta moves the inner product by exactly delta * other[j]
+    let delta = BinaryField128b::random(thread_rng());
+    code.encoding[37] += delta;
+    assert_eq!(code.inner_product(&other_code), delta * other_code.idx(37));
+    assert_eq!(
+        code.inner_product(&other_code),
+        other_code.inner_product(&code)
+    );
+}
+
+#[test]
+fn test_subsample() {
+    let (l, stride) = (5, 4);
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let subsampled = code.subsample(stride);
+    assert_eq!(subsampled.encoding.len(), code.encoding.len() / stride);
+
+    //Symbol i is the message evaluated at the domain point i * stride
+    for (i, val) in subsampled.encoding.iter().enumerate() {
+        let eval: BinaryField128b = poly
+            .iter()
+            .enumerate()
+            .map(|(j, coeff)| *coeff * novel_basis_eval(j, i * stride, &ntt))
+            .sum();
+        assert_eq!(*val, eval);
+        assert_eq!(
+            Code::domain_point(i * stride, &ntt).val() & (stride as u128 - 1),
+            0
+        );
+    }
+}
+
+#[test]
+fn test_new_from_packed_subfield() {
+    use binius_field::{PackedBinaryField4x32b, PackedBinaryField128x1b};
+
+    let l = 4;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let packed: Vec<PackedBinaryField128x1b> = (0..1 << l)
+        .map(|_| PackedBinaryField128x1b::random(thread_rng()))
+        .collect();
+    let unpacked: Vec<BinaryField1b> = packed.iter().flat_map(|p| p.iter()).collect();
+    assert_eq!(
+        Code::new_from_packed_subfield(&packed, &ntt),
+        Code::new(&unpacked, &ntt)
+    );
+
+    let packed: Vec<PackedBinaryField4x32b> = (0..1 << l)
+        .map(|_| PackedBinaryField4x32b::random(thread_rng()))
+        .collect();
+    let unpacked: Vec<BinaryField32b> = packed.iter().flat_map(|p| p.iter()).collect();
+    assert_eq!(
+        Code::new_from_packed_subfield(&packed, &ntt),
+        Code::new(&unpacked, &ntt)
+    );
+}
+
+#[test]
+fn test_new_coset_parallel() {
+    for l in [1, 4, 8] {
+        let poly: Vec<BinaryField128b> = (0..1 << l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+
+        let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE).unwrap();
+        assert_eq!(
+            Code::new_coset_parallel(&poly, &ntt),
+            Code::new(&poly, &ntt)
+        );
+
+        let ntt = ntt.multithreaded();
+        assert_eq!(
+            Code::new_coset_parallel(&poly, &ntt),
+            Code::new(&poly, &ntt)
+        );
+    }
+}
+
+#[test]
+fn test_new_async() {
+    use crate::utils::with_threads;
+
+    let l = 8;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let expected = Code::new(&poly, &ntt);
+
+    let ntt = Arc::new(ntt);
+    let message = Arc::new(poly);
+    assert_eq!(
+        Code::new_async(message.clone(), ntt.clone()).join(),
+        expected
+    );
+
+    //join on the only worker of a pool, which would deadlock if the encode needed that worker
+    let code = with_threads(1, || Code::new_async(message.clone(), ntt.clone()).join());
+    assert_eq!(code, expected);
+
+    let handle = Code::new_async(message, ntt);
+    let code = loop {
+        if let Some(code) = handle.try_poll() {
+            break code;
+        }
+        std::thread::yield_now();
+    };
+    assert_eq!(code, expected);
+    assert_eq!(handle.try_poll(), None);
+}
+
+#[test]
+fn test_tower_encoder() {
+    use binius_field::{BinaryField2b, BinaryField4b, BinaryField8b, BinaryField16b};
+
+    fn check<F>(l: usize, ntt: &(impl AdditiveNTT<BinaryField128b> + NttThreading))
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+    {
+        let message: Vec<F> = (0..1 << l).map(|_| F::random(thread_rng())).collect();
+        let lifted: Vec<BinaryField128b> = message.iter().map(|elem| (*elem).into()).collect();
+        assert_eq!(
+            TowerEncoder::new(ntt).encode(&message),
+            Code::new(&lifted, ntt)
+        );
+    }
+
+    let l = 5;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    check::<BinaryField1b>(l, &ntt);
+    check::<BinaryField2b>(l, &ntt);
+    check::<BinaryField4b>(l, &ntt);
+    check::<BinaryField8b>(l, &ntt);
+    check::<BinaryField16b>(l, &ntt);
+    check::<BinaryField32b>(l, &ntt);
+    check::<BinaryField64b>(l, &ntt);
+}
+
+#[test]
+fn test_sparse_code() {
+    let l = 5;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let mut encoding = vec![BinaryField128b::ZERO; 1 << (l + LOG_RATE)];
+    for idx in [0, 17, 100] {
+        encoding[idx] = BinaryField128b::random(thread_rng());
+    }
+    let code = Code { encoding };
+    let sparse = code.to_sparse();
+    assert_eq!(
+        sparse
+            .nonzeros
+            .iter()
+            .map(|(idx, _)| *idx)
+            .collect::<Vec<usize>>(),
+        vec![0, 17, 100]
+    );
+    assert_eq!(sparse.to_dense(), code);
+
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let code = Code::new(&poly, &ntt);
+    let sparse = code.to_sparse();
+    assert_eq!(sparse.nonzeros.len(), code.encoding.len());
+    assert_eq!(sparse.density(), 1.0);
+    assert_eq!(sparse.to_dense(), code);
+}
+
+#[test]
+fn test_fold_layers_lazy() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let challenges: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    let layers: Vec<Code<BinaryField128b>> =
+        code.clone().fold_layers_lazy(&challenges, &ntt).collect();
+
+    assert_eq!(layers.len(), l);
+    for (round, layer) in layers.iter().enumerate() {
+        assert_eq!(*layer, code.fold_rounds(&challenges[..round + 1], 0, &ntt));
+    }
+    assert_eq!(layers[l - 1].idx(0), code.fold_all(&challenges, &ntt));
+}
+
+#[test]
+fn test_symbols_at() {
+    let l = 8;
+    let poly: Vec<BinaryField32b> = (0..1 << l)
+        .map(|_| BinaryField32b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l - 2 + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let code = Code::new(&poly, &ntt);
+    let indices: Vec<usize> = (0..200)
+        .map(|_| thread_rng().gen_range(0..code.encoding.len()))
+        .collect();
+
+    let symbols = Code::symbols_at(&poly, &indices, &ntt);
+    for (idx, symbol) in indices.iter().zip(symbols.iter()) {
+        assert_eq!(*symbol, code.idx(*idx));
+        assert_eq!(*symbol, Code::symbol_at(&poly, *idx, &ntt));
+    }
+}
+
+#[test]
+fn test_transposed_code() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    let mut transposed = TransposedCode::from_code(&code);
+    assert_eq!(transposed.to_code(), code);
+
+    for round in 0..l {
+        let r = BinaryField128b::random(thread_rng());
+        code = code.fold_code(r, round, &ntt);
+        assert_eq!(
+            transposed
+                .fold_packed::<PackedBinaryField4x128b, _, _>(r, round, &ntt)
+                .to_code(),
+            code
+        );
+        transposed = transposed.fold(r, round, &ntt);
+        assert_eq!(transposed.to_code(), code);
+    }
+}
+
+#[test]
+fn test_is_zero() {
+    let l = 5;
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let mut poly = vec![BinaryField128b::ZERO; 1 << l];
+    assert!(Code::new(&poly, &ntt).is_zero());
+
+    poly[(1 << l) - 1] = BinaryField128b::ONE;
+    assert!(!Code::new(&poly, &ntt).is_zero());
+}
+
+#[test]
+fn test_shift() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(code.shift(BinaryField128b::ZERO, &ntt), code);
+
+    let c = BinaryField128b::random(thread_rng());
+    let shifted = code.shift(c, &ntt);
+    assert!(shifted.is_valid_codeword(&ntt));
+    assert_eq!(
+        shifted.decode(&ntt),
+        shift_novel_coeffs(&code.decode(&ntt), c, &ntt)
+    );
+
+    //Shifts compose additively
+    let d = BinaryField128b::random(thread_rng());
+    assert_eq!(shifted.shift(d, &ntt), code.shift(c + d, &ntt));
+
+    //A shift by a domain point permutes the symbols
+    let k = 13;
+    let shifted = code.shift(Code::domain_point(k, &ntt), &ntt);
+    for idx in 0..code.encoding.len() {
+        assert_eq!(shifted.idx(idx), code.idx(idx ^ k));
+    }
+}
+
+#[test]
+fn test_interpolate_from() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+
+    //RATE - 1 of every RATE symbols lost, the survivors spread over every coset
+    let known: Vec<(usize, BinaryField128b)> = (0..code.encoding.len())
+        .filter(|idx| idx % RATE == RATE - 1)
+        .map(|idx| (idx, code.idx(idx)))
+        .collect();
+    assert_eq!(known.len(), 1 << l);
+    assert_eq!(Code::interpolate_from(&known, l, &ntt).unwrap(), poly);
+
+    //Coset 0 itself needs no interpolation
+    let coset_0: Vec<(usize, BinaryField128b)> =
+        (0..1 << l).map(|idx| (idx, code.idx(idx))).collect();
+    assert_eq!(Code::interpolate_from(&coset_0, l, &ntt).unwrap(), poly);
+
+    let mut too_few = known.clone();
+    too_few.pop();
+    too_few.push(too_few[0]);
+    match Code::interpolate_from(&too_few, l, &ntt) {
+        Err(CodeError::TooFewSymbols { got, needed }) => {
+            assert_eq!((got, needed), ((1 << l) - 1, 1 << l))
+        }
+        other => panic!("expected TooFewSymbols, got {other:?}"),
+    }
+
+    let mut out_of_range = known.clone();
+    out_of_range.push((code.encoding.len(), BinaryField128b::ONE));
+    match Code::interpolate_from(&out_of_range, l, &ntt) {
+        Err(CodeError::PositionOutOfRange { position, len }) => {
+            assert_eq!((position, len), (RATE << l, RATE << l))
+        }
+        other => panic!("expected PositionOutOfRange, got {other:?}"),
+    }
+
+    assert!(matches!(
+        Code::interpolate_from(&known, usize::BITS as usize, &ntt),
+        Err(CodeError::DimensionOverflow { message_dim }) if message_dim == usize::BITS as usize
+    ));
+}
+
+#[test]
+fn test_frobenius() {
+    let l = 3;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(code.frobenius(0, &ntt), code);
+    assert_eq!(code.frobenius(128, &ntt), code);
+
+    let squared: Vec<BinaryField128b> = poly.iter().map(|coeff| *coeff * *coeff).collect();
+    assert_eq!(code.frobenius(1, &ntt), Code::new(&squared, &ntt));
+    assert_eq!(
+        code.frobenius(5, &ntt).frobenius(9, &ntt),
+        code.frobenius(14, &ntt)
+    );
+
+    let mut twisted = code.clone();
+    for _ in 0..128 {
+        twisted = twisted.frobenius(1, &ntt);
+    }
+    assert_eq!(twisted, code);
+}
+
+#[test]
+fn test_restrict_hypercube() {
+    use crate::utils::mle::multilinear_eval;
+
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(code.restrict_hypercube(&[], &ntt), code);
+
+    //Fixing every variable leaves the constant codeword of the evaluation at that vertex
+    let vertex = 0b10110;
+    let fixed: Vec<(usize, bool)> = (0..l).map(|i| (i, (vertex >> i) & 1 == 1)).collect();
+    let point: Vec<BinaryField128b> = fixed
+        .iter()
+        .map(|&(_, bit)| {
+            if bit {
+                BinaryField128b::ONE
+            } else {
+                BinaryField128b::ZERO
+            }
+        })
+        .collect();
+    let restricted = code.restrict_hypercube(&fixed, &ntt);
+    assert_eq!(
+        restricted.encoding,
+        vec![multilinear_eval(&poly, &point); RATE]
+    );
+
+    let restricted = code.restrict_hypercube(&[(3, true), (1, false)], &ntt);
+    assert!(restricted.is_valid_codeword(&ntt));
+    let expected: Vec<BinaryField128b> = (0..1 << l)
+        .filter(|i| (i >> 3) & 1 == 1 && (i >> 1) & 1 == 0)
+        .map(|i| poly[i])
+        .collect();
+    assert_eq!(restricted, Code::new(&expected, &ntt));
+
+    //Folding with a Boolean challenge fixes variable 0, over the domain of round 1
+    for bit in [false, true] {
+        let r = if bit {
+            BinaryField128b::ONE
+        } else {
+            BinaryField128b::ZERO
+        };
+        let folded = code.fold_code(r, 0, &ntt);
+        let coeffs = folded.decode_at_round(1, &ntt);
+        assert_eq!(
+            coeffs[..1 << (l - 1)],
+            code.restrict_hypercube(&[(0, bit)], &ntt).decode(&ntt)[..]
+        );
+    }
+}
+
+#[test]
+fn test_fold_one() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in 0..3 {
+        let r = BinaryField128b::random(thread_rng());
+        let folded_code = code.fold_code(r, round, &ntt);
+
+        for parent_idx in 1..folded_code.encoding.len() {
+            let (left, right) = (code.idx(parent_idx << 1), code.idx((parent_idx << 1) | 1));
+            assert_eq!(
+                fold_one(r, round, parent_idx, left, right, &ntt),
+                folded_code.idx(parent_idx)
+            );
+            //The left child position selects a different twiddle
+            if parent_idx < folded_code.encoding.len() >> 1 {
+                assert_ne!(
+                    fold_one(r, round, parent_idx << 1, left, right, &ntt),
+                    folded_code.idx(parent_idx)
+                );
+            }
+        }
+        code = folded_code;
+    }
+}
+
+#[test]
+fn test_fold_all_hashed() {
+    use crate::utils::merkle::MerkleRootHasher;
+
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
     let r: Vec<BinaryField128b> = (0..l)
-        .into_iter()
         .map(|_| BinaryField128b::random(thread_rng()))
         .collect();
 
-    let r_eq = LagrangeBases::gen_from_point(&r);
+    let (value, digest) = code.fold_all_hashed::<MerkleRootHasher, _, _>(&r, &ntt);
+    assert_eq!(value, code.fold_all(&r, &ntt));
 
-    let poly_eval: BinaryField128b = poly
-        .par_iter()
-        .zip(r_eq.vals)
-        .map(|(coeff, eq_val)| *coeff * eq_val)
-        .sum();
+    let mut layers = vec![code.clone()];
+    for (round, &challenge) in r.iter().enumerate() {
+        layers.push(layers[round].fold_code(challenge, round, &ntt));
+    }
+    let rolling_hash = |layers: &[Code<BinaryField128b>]| {
+        layers
+            .iter()
+            .fold(MerkleRootHasher::initial(), |running, layer| {
+                MerkleRootHasher::chain(&running, &MerkleRootHasher::layer_digest(&layer.encoding))
+            })
+    };
+    assert_eq!(rolling_hash(&layers), digest);
 
-    let mut folded_code = code.fold_code(r[0], 0, &ntt);
-    for round in 1..l {
-        folded_code = folded_code.fold_code(r[round], round, &ntt)
+    //Tampering with a single symbol of any one layer changes the rolling hash
+    for k in 0..layers.len() {
+        let mut tampered_layers = layers.clone();
+        tampered_layers[k].encoding[0] += BinaryField128b::ONE;
+        assert_ne!(rolling_hash(&tampered_layers), digest);
     }
+}
 
-    println!("{:?}", poly_eval);
-    println!("{:?}", folded_code)
+#[test]
+fn test_fold2() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in [0, 2, 4] {
+        let (r0, r1) = (
+            BinaryField128b::random(thread_rng()),
+            BinaryField128b::random(thread_rng()),
+        );
+        let folded_code = code.fold2(r0, r1, round, &ntt);
+
+        assert_eq!(
+            folded_code,
+            code.fold_code(r0, round, &ntt)
+                .fold_code(r1, round + 1, &ntt)
+        );
+        code = folded_code;
+    }
+    assert_eq!(code.encoding.len(), RATE << (l - 6));
 }
 
 #[test]
-fn test_ntt() {
-    let l = 11;
-    let mut poly: Vec<BinaryField64b> = (0..1 << l)
-        .into_par_iter()
+fn test_fold_code_power() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let r = BinaryField128b::random(thread_rng());
+    assert_eq!(
+        code.fold_code_power(r, 0, 1, &ntt),
+        code.fold_code(r, 0, &ntt)
+    );
+
+    let (r2, r4) = (r * r, r * r * r * r);
+    assert_eq!(
+        code.fold_code_power(r, 0, 3, &ntt),
+        code.fold_rounds(&[r, r2, r4], 0, &ntt)
+    );
+
+    let folded_code = code.fold_code(BinaryField128b::random(thread_rng()), 0, &ntt);
+    assert_eq!(
+        folded_code.fold_code_power(r, 1, 2, &ntt),
+        folded_code.fold_rounds(&[r, r2], 1, &ntt)
+    );
+}
+
+#[test]
+fn test_from_leaves() {
+    use crate::utils::merkle::{MerkleTree, hash_leaf};
+
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    for leaf_arity in [1, 2, RATE] {
+        let merkle_tree = MerkleTree::build(&code.encoding, leaf_arity);
+        let leaves: Vec<&[BinaryField128b]> = (0..code.encoding.len() / leaf_arity)
+            .map(|leaf_index| {
+                let (leaf, _, _) =
+                    merkle_tree.open(&code.encoding, leaf_index * leaf_arity, leaf_arity);
+                leaf
+            })
+            .collect();
+
+        let depth = merkle_tree.data.len() - 1;
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            assert_eq!(hash_leaf(leaf), merkle_tree.data[&depth][leaf_index]);
+        }
+        assert_eq!(Code::from_leaves(&leaves, leaf_arity), code);
+    }
+}
+
+#[test]
+fn test_fold_all_multi() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let p: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    let q: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(
+        code.fold_all_multi(&[&p, &q], &ntt),
+        vec![code.fold_all(&p, &ntt), code.fold_all(&q, &ntt)]
+    );
+}
+
+#[test]
+fn test_code_context() {
+    let max_dim = 8;
+    let context = CodeContext::new(max_dim).unwrap();
+
+    for l in [1, 4, max_dim] {
+        let (poly, ntt) = random_poly_and_ntt(l);
+
+        let code = context.encode(&poly);
+        assert_eq!(code, Code::new(&poly, &ntt));
+        assert_eq!(code.decode(context.ntt()), poly);
+    }
+
+    let poly: Vec<BinaryField64b> = (0..1 << 5)
         .map(|_| BinaryField64b::random(thread_rng()))
         .collect();
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(4 + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+    assert_eq!(context.encode(&poly), Code::new(&poly, &ntt));
+}
 
-    let ntt = SingleThreadedNTT::<BinaryField32b>::new(13)
+#[test]
+#[should_panic(expected = "exceeds the context's maximum")]
+fn test_code_context_partial_symbol() {
+    //One BinaryField64b past 2^8 full symbols repacks to 2^8 + 1 symbols
+    let context = CodeContext::new(8).unwrap();
+    let poly = vec![BinaryField64b::ONE; (2 << 8) + 1];
+    context.encode(&poly);
+}
+
+#[test]
+fn test_with_threads() {
+    use crate::utils::with_threads;
+
+    let l = 10;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = with_threads(1, || {
+        assert_eq!(rayon::current_num_threads(), 1);
+        Code::new(&poly, &ntt)
+    });
+    let parallel_code = with_threads(4, || {
+        assert_eq!(rayon::current_num_threads(), 4);
+        Code::new(&poly, &ntt)
+    });
+    assert_eq!(code, parallel_code);
+}
+
+#[test]
+fn test_required_challenges() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(code.required_challenges(), l);
+
+    let r: Vec<BinaryField128b> = (0..l + 1)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+    assert!(matches!(
+        code.fold_all_checked(&r[..l - 1], false, &ntt),
+        Err(CodeError::ChallengeCountMismatch {
+            got,
+            expected
+        }) if got == l - 1 && expected == l
+    ));
+    assert!(matches!(
+        code.fold_all_checked(&r, false, &ntt),
+        Err(CodeError::ChallengeCountMismatch {
+            got,
+            expected
+        }) if got == l + 1 && expected == l
+    ));
+    assert_eq!(
+        code.fold_all_checked(&r[..l], false, &ntt).unwrap(),
+        code.fold_all(&r[..l], &ntt)
+    );
+}
+
+#[test]
+fn test_difference() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let difference = code.difference();
+    assert_eq!(
+        difference,
+        Code::linear_combine(
+            &[code.clone(), code.shift(BinaryField128b::ONE, &ntt)],
+            &[BinaryField128b::ONE, BinaryField128b::ONE]
+        )
+    );
+
+    let expected: Vec<BinaryField128b> = (0..1 << l)
+        .map(|i| match i & 1 {
+            0 => poly[i | 1],
+            _ => BinaryField128b::ZERO,
+        })
+        .collect();
+    assert_eq!(difference.decode(&ntt), expected);
+}
+
+#[test]
+fn test_digest() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let root = MerkleTree::build(&code.encoding, 2).get_root();
+    let header = CodeHeader {
+        message_dim: l,
+        rate: RATE,
+        coset_order: CosetOrder::Natural,
+    };
+    assert_eq!(
+        code.digest(CosetOrder::Natural),
+        bind_header(&root, &header)
+    );
+
+    //The same symbols read as a message of half the size at twice the rate
+    let reinterpreted = CodeHeader {
+        message_dim: l - 1,
+        rate: 2 * RATE,
+        ..header
+    };
+    assert_ne!(
+        bind_header(&root, &reinterpreted),
+        code.digest(CosetOrder::Natural)
+    );
+    assert_ne!(
+        bind_header(
+            &root,
+            &CodeHeader {
+                rate: 2 * RATE,
+                ..header
+            }
+        ),
+        code.digest(CosetOrder::Natural)
+    );
+    assert_ne!(
+        code.digest(CosetOrder::BitReversed),
+        code.digest(CosetOrder::Natural)
+    );
+}
+
+#[test]
+fn test_describe() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let description = Code::new(&poly, &ntt).describe();
+    assert_eq!(
+        description,
+        CodeDescription {
+            message_dim: 6,
+            codeword_len: 256,
+            rate: 4,
+            num_cosets: 4,
+            footprint_bytes: 4096,
+            num_fold_rounds: 6,
+        }
+    );
+
+    let table = description.to_string();
+    assert_eq!(table.lines().count(), 6);
+    assert!(table.lines().next().unwrap().starts_with("message_dim"));
+    assert!(table.lines().last().unwrap().ends_with(" 6"));
+}
+
+#[test]
+fn test_footprint() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+    let challenges: Vec<BinaryField128b> = (0..l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let code = Code::new(&poly, &ntt);
+    assert_eq!(code.footprint(), code.encoding.len() * 16);
+
+    let tree_bytes: usize = code.footprint()
+        + code
+            .clone()
+            .fold_layers_lazy(&challenges, &ntt)
+            .map(|layer| layer.footprint())
+            .sum::<usize>();
+    assert_eq!(fold_tree_bytes(l, RATE), tree_bytes);
+}
+
+#[test]
+fn test_fold_code_into_buf() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    let mut buf = vec![BinaryField128b::ZERO; code.encoding.len() >> 1];
+    for round in 0..3 {
+        let r = BinaryField128b::random(thread_rng());
+        let half_len = code.encoding.len() >> 1;
+        code.fold_code_into_buf(r, round, &ntt, &mut buf[..half_len])
+            .unwrap();
+
+        let folded_code = code.fold_code(r, round, &ntt);
+        assert_eq!(buf[..half_len], folded_code.encoding[..]);
+        code = folded_code;
+    }
+
+    let r = BinaryField128b::random(thread_rng());
+    assert!(matches!(
+        code.fold_code_into_buf(r, 3, &ntt, &mut buf),
+        Err(CodeError::OutputLength { expected, actual }) if expected == code.encoding.len() >> 1 && actual == buf.len()
+    ));
+}
+
+#[test]
+fn test_new_with_message_evals() {
+    let l = 5;
+    let poly: Vec<BinaryField64b> = (0..1 << (l + 1))
+        .map(|_| BinaryField64b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
         .unwrap()
         .multithreaded();
 
-    let res = ntt.forward_transform_ext(&mut poly, 0);
-    match res {
-        Ok(()) => println!("ok"),
-        Err(error) => println!("{:?}", error),
+    let (code, message_evals) = Code::new_with_message_evals(&poly, &ntt);
+    assert_eq!(code, Code::new(&poly, &ntt));
+
+    let coeffs = code.decode(&ntt);
+    let indices: Vec<usize> = (0..1 << l).collect();
+    assert_eq!(message_evals, Code::symbols_at(&coeffs, &indices, &ntt));
+}
+
+#[test]
+fn test_fold_bytes() {
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let mut code = Code::new(&poly, &ntt);
+    for round in 0..3 {
+        let r = BinaryField128b::random(thread_rng());
+        let folded_bytes = fold_bytes(&code.to_bytes(), r, round, &ntt);
+
+        code = code.fold_code(r, round, &ntt);
+        assert_eq!(folded_bytes, code.to_bytes());
+    }
+}
+
+#[test]
+fn test_fold_chunks() {
+    let l = 5;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let r = BinaryField128b::random(thread_rng());
+    let folded_code = code.fold_code(r, 0, &ntt);
+
+    let (left, right) = code.encoding.split_at(64);
+    assert_eq!(
+        fold_chunks(&[left, right], r, 0, &ntt),
+        folded_code.encoding
+    );
+
+    //Odd boundaries, with an empty chunk between the halves of a pair
+    let (left, right) = code.encoding.split_at(37);
+    let (middle, right) = right.split_at(50);
+    let chunks: [&[BinaryField128b]; 4] = [left, &[], middle, right];
+    assert_eq!(fold_chunks(&chunks, r, 0, &ntt), folded_code.encoding);
+}
+
+#[test]
+fn test_fold_all_fresh() {
+    use crate::utils::mle::multilinear_eval;
+
+    let l = 6;
+    let (poly, ntt) = random_poly_and_ntt(l);
+
+    let code = Code::new(&poly, &ntt);
+    let encoding = code.encoding.clone();
+    for _ in 0..2 {
+        let point: Vec<BinaryField128b> = (0..l)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        assert_eq!(
+            code.fold_all_fresh(&point, &ntt),
+            multilinear_eval(&poly, &point)
+        );
+        assert_eq!(code.encoding, encoding);
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{ collections::HashMap, fmt::Debug };
 
 use binius_field::{
     BinaryField,
@@ -8,20 +8,44 @@ use binius_field::{
     Field,
     TowerField,
 };
-use rayon::{iter::{ IntoParallelIterator, ParallelIterator }, slice::ParallelSlice};
+use rayon::{ iter::ParallelIterator, slice::ParallelSlice };
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
 use sha3::{ Digest, Keccak256, digest::{ consts::U32, generic_array::GenericArray } };
 use tracing::instrument;
 
+use crate::{ utils::code::CosetOrder, verifier::VerifyError };
+
 //Wrapper struct for hash digests
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Hash(pub GenericArray<u8, U32>);
 
+//GenericArray has no serde support of its own, so the digest goes through serde as its 32 bytes.
+impl Serialize for Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: [u8; 32] = self.0.into();
+        bytes.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Hash, D::Error> {
+        Ok(Hash(GenericArray::from(<[u8; 32]>::deserialize(deserializer)?)))
+    }
+}
+
 //Struct for Merkle Tree. Backing type chosen to be a hashmap for average case constant insertions and indexing.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MerkleTree {
     pub data: HashMap<usize, Vec<Hash>>,
 }
 
+//Multi-proof for several leaves of one tree: the siblings that cannot be recomputed from the opened leaves, layer by layer from the leaves up and
+//left to right within a layer. Shared upper nodes appear once instead of once per path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchProof {
+    pub hashes: Vec<Hash>,
+}
+
 //Struct for Merkle Tree. Backing type chosen to be a hashmap for average case constant insertions and indexing.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct VectorCommitment {
@@ -46,6 +70,61 @@ impl MerkleTree {
     pub fn get_root(&self) -> Hash {
         self.data.get(&0).unwrap()[0].clone()
     }
+
+    //Builds the tree over vals with leaf_arity consecutive symbols hashed into each leaf, e.g. 2 for the fold pairs or RATE for the coset siblings.
+    //Larger leaves shorten the paths at the cost of revealing every symbol of an opened leaf.
+    #[instrument(skip_all, name = "build_merkle_tree", level = "debug")]
+    pub fn build(vals: &[BinaryField128b], leaf_arity: usize) -> MerkleTree {
+        merklize(compute_leaf_hashes_with_arity(vals, leaf_arity))
+    }
+
+    //Opens the leaves at indices, which must be sorted and distinct, as one BatchProof checked by verify_batch.
+    pub fn open_batch(&self, indices: &[usize]) -> BatchProof {
+        assert!(!indices.is_empty(), "No leaves to open");
+        assert!(
+            indices.windows(2).all(|pair| pair[0] < pair[1]),
+            "Leaf indices must be sorted and distinct"
+        );
+
+        let tree_depth = self.data.len() - 1;
+        let mut known = indices.to_vec();
+        let mut hashes = Vec::new();
+
+        for depth in (1..=tree_depth).rev() {
+            let layer = self.data.get(&depth).unwrap();
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let sibling = known[i] ^ 1;
+                //Both children are known when the sibling is the next index, otherwise the sibling goes in the proof
+                if i + 1 < known.len() && known[i + 1] == sibling {
+                    i += 2;
+                } else {
+                    hashes.push(layer[sibling].clone());
+                    i += 1;
+                }
+                parents.push(known[i - 1] >> 1);
+            }
+            known = parents;
+        }
+
+        BatchProof { hashes }
+    }
+
+    //Opens the leaf holding the symbol at symbol_idx: all leaf_arity symbols of the leaf, the leaf's index and its Merkle path.
+    pub fn open<'a>(
+        &self,
+        vals: &'a [BinaryField128b],
+        symbol_idx: usize,
+        leaf_arity: usize
+    ) -> (&'a [BinaryField128b], usize, Vec<Hash>) {
+        let leaf_index = symbol_idx / leaf_arity;
+        (
+            &vals[leaf_index * leaf_arity..(leaf_index + 1) * leaf_arity],
+            leaf_index,
+            self.get_merkle_path(leaf_index),
+        )
+    }
 }
 
 #[inline(always)]
@@ -77,8 +156,19 @@ pub fn hash_concatenation(data1: &Hash, data2: &Hash) -> Hash {
     Hash(Keccak256::digest(val))
 }
 
+//Layers with fewer hashes than this are built on the calling thread. A pair costs one Keccak256 of 64 bytes, a few hundred nanoseconds, so a layer of
+//2^10 hashes is a few hundred microseconds of work against the few microseconds rayon takes to split and join it, and the layers below are too short
+//for the split to pay off. The merklize_threshold group of benches/merkle.rs sweeps this on a 2^22 codeword, rerun it when changing the value.
+pub const PARALLEL_LAYER_THRESHOLD: usize = 1 << 10;
+
 #[instrument(skip_all, name = "make_merkle_tree", level = "debug")]
 pub fn merklize(leaf_hashes: Vec<Hash>) -> MerkleTree {
+    merklize_with_threshold(leaf_hashes, PARALLEL_LAYER_THRESHOLD)
+}
+
+//merklize with the layers of at least parallel_threshold hashes built in parallel, usize::MAX for a fully serial build. The tree is the same for any
+//threshold, only the split of the work changes.
+pub fn merklize_with_threshold(leaf_hashes: Vec<Hash>, parallel_threshold: usize) -> MerkleTree {
     assert!(
         leaf_hashes.len().is_power_of_two(),
         "Leaf hashes are not power of 2, cannot make Merkle Tree"
@@ -93,12 +183,18 @@ pub fn merklize(leaf_hashes: Vec<Hash>) -> MerkleTree {
     for depth in (0..tree_depth).rev() {
         let lower_layer = tree.get(&(depth + 1)).unwrap();
 
-        let current_layer_size = lower_layer.len() / 2;
-
-        let current_layer: Vec<Hash> = (0..current_layer_size)
-            .into_par_iter()
-            .map(|i| hash_concatenation(&lower_layer[2 * i], &lower_layer[2 * i + 1]))
-            .collect();
+        //Small layers cost less to hash than to split across threads, the chunks are independent either way so the layer does not depend on the split
+        let current_layer: Vec<Hash> = if lower_layer.len() >= parallel_threshold {
+            lower_layer
+                .par_chunks_exact(2)
+                .map(|pair| hash_concatenation(&pair[0], &pair[1]))
+                .collect()
+        } else {
+            lower_layer
+                .chunks_exact(2)
+                .map(|pair| hash_concatenation(&pair[0], &pair[1]))
+                .collect()
+        };
 
         tree.insert(depth, current_layer);
     }
@@ -151,18 +247,210 @@ pub fn verify_merkle_path(
     assert_eq!(hash, commitment.root);
 }
 
+//Non-panicking verify_merkle_path for proofs from an untrusted prover.
+pub fn check_merkle_path(
+    commitment: &VectorCommitment,
+    leaf_hash: Hash,
+    leaf_index: usize,
+    merkle_path: &[Hash]
+) -> bool {
+    if merkle_path.len() != commitment.depth || leaf_index >> commitment.depth != 0 {
+        return false;
+    }
+
+    let mut hash = leaf_hash;
+    for (d, sibling) in merkle_path.iter().enumerate() {
+        if ((leaf_index >> d) & 1) == 0 {
+            hash = hash_concatenation(&hash, sibling);
+        } else {
+            hash = hash_concatenation(sibling, &hash);
+        }
+    }
+
+    hash == commitment.root
+}
+
+//Depth of the tree committing to a codeword of codeword_len symbols packed leaf_arity to a leaf.
+pub fn tree_depth(codeword_len: usize, leaf_arity: usize) -> usize {
+    (codeword_len / leaf_arity).trailing_zeros() as usize
+}
+
+//check_merkle_path against a depth the verifier derived itself with tree_depth, instead of the one carried by the commitment. A path with a missing or
+//extra node is BadProofLength before anything is hashed, even if the nodes it does have would reach the root.
+pub fn verify_merkle_proof(
+    commitment: &VectorCommitment,
+    leaf_hash: Hash,
+    leaf_index: usize,
+    merkle_path: &[Hash],
+    expected_depth: usize
+) -> Result<bool, VerifyError> {
+    if merkle_path.len() != expected_depth {
+        return Err(VerifyError::BadProofLength {
+            got: merkle_path.len(),
+            expected: expected_depth,
+        });
+    }
+
+    Ok(commitment.depth == expected_depth && check_merkle_path(commitment, leaf_hash, leaf_index, merkle_path))
+}
+
+//Checks the opening of the leaf holding the fold pair of parent_idx, symbols 2 * parent_idx and 2 * parent_idx + 1, and returns the pair read from its
+//expected position in the leaf. The leaf arity is taken from opened_leaf and must be even so that a pair never straddles two leaves. The leaf index is
+//derived from parent_idx rather than trusted from the prover, so a valid path to any other leaf, or the right leaf with its symbols permuted, is None.
+//The path is checked through verify_merkle_proof against the depth of a codeword_len symbol tree, so a commitment claiming a shallower tree is None too.
+pub fn verify_fold_opening(
+    commitment: &VectorCommitment,
+    codeword_len: usize,
+    parent_idx: usize,
+    opened_leaf: &[BinaryField128b],
+    proof: &[Hash]
+) -> Option<(BinaryField128b, BinaryField128b)> {
+    let leaf_arity = opened_leaf.len();
+    if leaf_arity < 2 || !leaf_arity.is_power_of_two() || codeword_len < leaf_arity {
+        return None;
+    }
+
+    let symbol_idx = parent_idx.checked_mul(2)?;
+    if symbol_idx >= codeword_len {
+        return None;
+    }
+    let (leaf_index, offset) = (symbol_idx / leaf_arity, symbol_idx % leaf_arity);
+    let expected_depth = tree_depth(codeword_len, leaf_arity);
+    if !verify_merkle_proof(commitment, hash_leaf(opened_leaf), leaf_index, proof, expected_depth).ok()? {
+        return None;
+    }
+
+    Some((opened_leaf[offset], opened_leaf[offset | 1]))
+}
+
+//Checks a BatchProof of the leaf hashes leaves at the sorted, distinct indices against commitment, consuming the proof in the order open_batch wrote it.
+pub fn verify_batch(
+    commitment: &VectorCommitment,
+    indices: &[usize],
+    leaves: &[Hash],
+    proof: &BatchProof
+) -> bool {
+    if indices.is_empty() || indices.len() != leaves.len() || commitment.depth >= usize::BITS as usize ||
+        !indices.windows(2).all(|pair| pair[0] < pair[1]) ||
+        indices[indices.len() - 1] >> commitment.depth != 0
+    {
+        return false;
+    }
+
+    let mut nodes: Vec<(usize, Hash)> = indices.iter().copied().zip(leaves.iter().cloned()).collect();
+    let mut proof_hashes = proof.hashes.iter();
+
+    for _ in 0..commitment.depth {
+        let mut parents = Vec::with_capacity(nodes.len());
+        let mut i = 0;
+        while i < nodes.len() {
+            let (idx, hash) = &nodes[i];
+            let parent = if i + 1 < nodes.len() && nodes[i + 1].0 == (idx ^ 1) {
+                i += 2;
+                hash_concatenation(hash, &nodes[i - 1].1)
+            } else {
+                let sibling = match proof_hashes.next() {
+                    Some(sibling) => sibling,
+                    None => return false,
+                };
+                i += 1;
+                if (idx & 1) == 0 {
+                    hash_concatenation(hash, sibling)
+                } else {
+                    hash_concatenation(sibling, hash)
+                }
+            };
+            parents.push((idx >> 1, parent));
+        }
+        nodes = parents;
+    }
+
+    proof_hashes.next().is_none() && nodes[0].1 == commitment.root
+}
+
 #[instrument(skip_all, name = "compute leaf hashes", level="debug")]
 pub fn compute_leaf_hashes(vals: &Vec<BinaryField128b>)->Vec<Hash>{
-    vals.par_chunks(2)
-        .map(|pair| {
-            let mut hasher = Keccak256::new();
-            hasher.update(pair[0].val().to_le_bytes());
-            hasher.update(pair[1].val().to_le_bytes());
-
-            Hash(hasher.finalize())
-        })
+    compute_leaf_hashes_with_arity(vals, 2)
+}
+
+pub fn compute_leaf_hashes_with_arity(vals: &[BinaryField128b], leaf_arity: usize) -> Vec<Hash> {
+    assert!(
+        leaf_arity > 0 && vals.len() % leaf_arity == 0,
+        "Leaf arity {} does not divide the number of symbols {}",
+        leaf_arity,
+        vals.len()
+    );
+
+    vals.par_chunks(leaf_arity)
+        .map(hash_leaf)
         .collect()
 }
+
+//Hash of the symbols of one leaf, their little endian bytes in order.
+pub fn hash_leaf(leaf: &[BinaryField128b]) -> Hash {
+    let mut hasher = Keccak256::new();
+    for symbol in leaf {
+        hasher.update(symbol.val().to_le_bytes());
+    }
+    Hash(hasher.finalize())
+}
+
+//Parameters a committed codeword is to be read under. The Merkle root alone only binds the symbols, so the same root could be opened as a codeword of a
+//different rate, dimension or coset layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeHeader {
+    pub message_dim: usize,
+    pub rate: usize,
+    pub coset_order: CosetOrder,
+}
+
+//Domain separated digest of a commitment: Keccak256 of the header, message_dim and rate as little endian u64 then one byte 0 for Natural and 1 for
+//BitReversed, absorbed before the Merkle root of the symbols. Openings are still checked against the root itself, the digest is what a transcript
+//should observe so that a different header gives different challenges.
+pub fn bind_header(root: &Hash, header: &CodeHeader) -> Hash {
+    let mut hasher = Keccak256::new();
+    hasher.update((header.message_dim as u64).to_le_bytes());
+    hasher.update((header.rate as u64).to_le_bytes());
+    hasher.update([
+        match header.coset_order {
+            CosetOrder::Natural => 0,
+            CosetOrder::BitReversed => 1,
+        },
+    ]);
+    hasher.update(root.0);
+    Hash(hasher.finalize())
+}
+//Hash used by Code::fold_all_hashed to chain fold layers: every layer is reduced to layer_digest and absorbed into the running digest with chain,
+//starting from initial. Any layer changing changes its digest and with it every later link, so one comparison of the final digest covers the tree.
+pub trait SymbolHasher {
+    type Digest: Clone + Debug + PartialEq + Eq;
+
+    fn initial() -> Self::Digest;
+    fn layer_digest(layer: &[BinaryField128b]) -> Self::Digest;
+    fn chain(running: &Self::Digest, layer_digest: &Self::Digest) -> Self::Digest;
+}
+
+//SymbolHasher whose layer digest is the Merkle root of the layer over pair leaves, the root commit_oracle sends for it, so a verifier holding the
+//commitment and the layer roots recomputes the chain without the layers. A link is Keccak256 of the running digest then the root, from all zeros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleRootHasher;
+
+impl SymbolHasher for MerkleRootHasher {
+    type Digest = Hash;
+
+    fn initial() -> Hash {
+        Hash(GenericArray::default())
+    }
+
+    fn layer_digest(layer: &[BinaryField128b]) -> Hash {
+        MerkleTree::build(layer, 2).get_root()
+    }
+
+    fn chain(running: &Hash, layer_digest: &Hash) -> Hash {
+        hash_concatenation(running, layer_digest)
+    }
+}
+
 pub mod tests {
     use rand::Rng;
 
@@ -206,4 +494,195 @@ pub mod tests {
 
         verify_merkle_path(&commitment, leaf_hashes[idx].clone(), idx, &merkle_path);
     }
+
+    #[test]
+    fn leaf_arity_test() {
+        use rand::thread_rng;
+
+        use crate::utils::code::RATE;
+
+        let vals: Vec<BinaryField128b> = (0..1 << 8)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+
+        for leaf_arity in [1, RATE, 8] {
+            let merkle_tree = MerkleTree::build(&vals, leaf_arity);
+            let commitment = VectorCommitment {
+                root: merkle_tree.get_root(),
+                depth: (vals.len() / leaf_arity).trailing_zeros() as usize,
+            };
+
+            let symbol_idx = thread_rng().gen_range(0..vals.len());
+            let (leaf, leaf_index, merkle_path) = merkle_tree.open(&vals, symbol_idx, leaf_arity);
+
+            assert_eq!(leaf.len(), leaf_arity);
+            assert_eq!(leaf[symbol_idx % leaf_arity], vals[symbol_idx]);
+            assert_eq!(merkle_path.len(), 8 - leaf_arity.trailing_zeros() as usize);
+            verify_merkle_path(&commitment, hash_leaf(leaf), leaf_index, &merkle_path);
+        }
+
+        assert_eq!(MerkleTree::build(&vals, 2), merklize(compute_leaf_hashes(&vals)));
+    }
+
+    #[test]
+    fn verify_merkle_proof_test() {
+        use rand::thread_rng;
+
+        let vals: Vec<BinaryField128b> = (0..1 << 8)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let merkle_tree = MerkleTree::build(&vals, 2);
+        let depth = tree_depth(vals.len(), 2);
+        let commitment = VectorCommitment {
+            root: merkle_tree.get_root(),
+            depth,
+        };
+
+        let symbol_idx = thread_rng().gen_range(0..vals.len());
+        let (leaf, leaf_index, merkle_path) = merkle_tree.open(&vals, symbol_idx, 2);
+        let leaf_hash = hash_leaf(leaf);
+        assert_eq!(depth, 7);
+        assert_eq!(verify_merkle_proof(&commitment, leaf_hash.clone(), leaf_index, &merkle_path, depth), Ok(true));
+
+        //The first depth nodes of the longer path still hash to the root
+        let mut extra = merkle_path.clone();
+        extra.push(merkle_path[0].clone());
+        assert_eq!(
+            verify_merkle_proof(&commitment, leaf_hash.clone(), leaf_index, &extra, depth),
+            Err(VerifyError::BadProofLength { got: depth + 1, expected: depth })
+        );
+
+        let mut missing = merkle_path.clone();
+        missing.pop();
+        assert_eq!(
+            verify_merkle_proof(&commitment, leaf_hash.clone(), leaf_index, &missing, depth),
+            Err(VerifyError::BadProofLength { got: depth - 1, expected: depth })
+        );
+
+        //A prover-supplied commitment claiming a shallower tree does not shorten the path the verifier accepts
+        let shallow = VectorCommitment {
+            root: merkle_tree.get_root(),
+            depth: depth - 1,
+        };
+        assert_eq!(verify_merkle_proof(&shallow, leaf_hash, leaf_index, &missing, depth), Err(VerifyError::BadProofLength {
+            got: depth - 1,
+            expected: depth,
+        }));
+    }
+
+    #[test]
+    fn verify_fold_opening_test() {
+        use rand::thread_rng;
+
+        let vals: Vec<BinaryField128b> = (0..1 << 8)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+
+        for leaf_arity in [2, 8] {
+            let merkle_tree = MerkleTree::build(&vals, leaf_arity);
+            let commitment = VectorCommitment {
+                root: merkle_tree.get_root(),
+                depth: (vals.len() / leaf_arity).trailing_zeros() as usize,
+            };
+
+            let parent_idx = thread_rng().gen_range(0..vals.len() >> 1);
+            let (leaf, leaf_index, merkle_path) = merkle_tree.open(&vals, parent_idx << 1, leaf_arity);
+
+            assert_eq!(
+                verify_fold_opening(&commitment, vals.len(), parent_idx, leaf, &merkle_path),
+                Some((vals[parent_idx << 1], vals[(parent_idx << 1) | 1]))
+            );
+
+            //Symbols of the right leaf in the wrong order
+            let mut shuffled = leaf.to_vec();
+            shuffled.swap(0, 1);
+            assert_eq!(verify_fold_opening(&commitment, vals.len(), parent_idx, &shuffled, &merkle_path), None);
+
+            //A valid opening of a different leaf
+            let other_leaf = leaf_index ^ 1;
+            let other_path = merkle_tree.get_merkle_path(other_leaf);
+            let other_vals = &vals[other_leaf * leaf_arity..(other_leaf + 1) * leaf_arity];
+            assert_eq!(verify_fold_opening(&commitment, vals.len(), parent_idx, other_vals, &other_path), None);
+
+            //The top node of the path dropped and the commitment claiming a tree one level shallower
+            let shallow = VectorCommitment {
+                root: merkle_tree.get_root(),
+                depth: commitment.depth - 1,
+            };
+            let short_path = &merkle_path[..merkle_path.len() - 1];
+            assert_eq!(verify_fold_opening(&shallow, vals.len(), parent_idx, leaf, short_path), None);
+        }
+    }
+
+    #[test]
+    fn parallel_build_determinism_test() {
+        use rand::thread_rng;
+        use rayon::ThreadPoolBuilder;
+
+        //Large enough that the upper layers cross PARALLEL_LAYER_THRESHOLD
+        let vals: Vec<BinaryField128b> = (0..1 << 14)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+
+        let serial_layers = {
+            let mut layers = vec![vals.chunks(2).map(hash_leaf).collect::<Vec<Hash>>()];
+            while layers.last().unwrap().len() > 1 {
+                let layer = layers
+                    .last()
+                    .unwrap()
+                    .chunks(2)
+                    .map(|pair| hash_concatenation(&pair[0], &pair[1]))
+                    .collect();
+                layers.push(layer);
+            }
+            layers
+        };
+
+        for num_threads in [1, 2, 8] {
+            let thread_pool = ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap();
+            let merkle_tree = thread_pool.install(|| MerkleTree::build(&vals, 2));
+
+            assert_eq!(merkle_tree.get_root(), serial_layers.last().unwrap()[0]);
+            for (i, layer) in serial_layers.iter().rev().enumerate() {
+                assert_eq!(merkle_tree.data.get(&i).unwrap(), layer);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_open_test() {
+        use rand::thread_rng;
+
+        let vals: Vec<BinaryField128b> = (0..1 << 11)
+            .map(|_| BinaryField128b::random(thread_rng()))
+            .collect();
+        let merkle_tree = MerkleTree::build(&vals, 2);
+        let commitment = VectorCommitment { root: merkle_tree.get_root(), depth: 10 };
+
+        let mut indices: Vec<usize> = (0..32).map(|_| thread_rng().gen_range(0..1 << 10)).collect();
+        indices.sort();
+        indices.dedup();
+        let leaves: Vec<Hash> = indices
+            .iter()
+            .map(|idx| hash_leaf(&vals[2 * idx..2 * idx + 2]))
+            .collect();
+
+        let mut individual_size = 0;
+        for (idx, leaf) in indices.iter().zip(leaves.iter()) {
+            let merkle_path = merkle_tree.get_merkle_path(*idx);
+            assert!(check_merkle_path(&commitment, leaf.clone(), *idx, &merkle_path));
+            individual_size += merkle_path.len();
+        }
+
+        let batch_proof = merkle_tree.open_batch(&indices);
+        assert!(verify_batch(&commitment, &indices, &leaves, &batch_proof));
+        assert!(batch_proof.hashes.len() < individual_size);
+
+        let mut tampered_leaves = leaves.clone();
+        tampered_leaves[0] = hash_leaf(&[BinaryField128b::ZERO, BinaryField128b::ZERO]);
+        assert!(!verify_batch(&commitment, &indices, &tampered_leaves, &batch_proof));
+
+        let too_deep = VectorCommitment { root: merkle_tree.get_root(), depth: usize::BITS as usize };
+        assert!(!verify_batch(&too_deep, &indices, &leaves, &batch_proof));
+    }
 }
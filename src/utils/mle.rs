@@ -255,6 +255,12 @@ impl LagrangeBases {
     pub fn idx(&self, idx: usize) -> BinaryField128b {
         self.vals[idx]
     }
+
+    //Multilinear evaluation of message at the point the bases were generated from.
+    pub fn evaluate(&self, message: &[BinaryField128b]) -> BinaryField128b {
+        assert_eq!(message.len(), self.vals.len(), "Message length does not match the eq table");
+        compute_dot_product(&self.vals, message)
+    }
 }
 
 fn compute_eq(r: &[BinaryField128b]) -> Vec<BinaryField128b> {
@@ -280,6 +286,28 @@ fn compute_eq(r: &[BinaryField128b]) -> Vec<BinaryField128b> {
     bases
 }
 
+//Multilinear evaluation of message at point, folding the lowest variable away per coordinate of point without building the eq table or a codeword.
+//Equals Code::new(message, ntt).fold_all(point, ntt) and LagrangeBases::gen_from_point(point).evaluate(message).
+#[instrument(skip_all, name = "multilinear eval", level = "debug")]
+pub fn multilinear_eval(message: &[BinaryField128b], point: &[BinaryField128b]) -> BinaryField128b {
+    assert_eq!(
+        message.len(),
+        1 << point.len(),
+        "Message of length {} is not a multilinear in {} variables",
+        message.len(),
+        point.len()
+    );
+
+    let mut vals = message.to_vec();
+    for r in point.iter() {
+        vals = vals
+            .par_chunks(2)
+            .map(|pair| pair[0] + *r * (pair[0] + pair[1]))
+            .collect();
+    }
+    vals[0]
+}
+
 pub fn compute_dot_product(
     scalars: &[BinaryField128b],
     vals: &[BinaryField128b]
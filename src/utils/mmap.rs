@@ -0,0 +1,100 @@
+use std::{fs::OpenOptions, mem::size_of, path::Path, slice};
+
+use binius_field::{BinaryField, BinaryField128b, ExtensionField, TowerField};
+use binius_ntt::AdditiveNTT;
+use memmap2::MmapMut;
+use tracing::instrument;
+
+use crate::utils::code::{Code, CodeError, RATE, repack};
+
+//Codeword backed by a memory-mapped file, for codewords that fit on disk but not in RAM.
+pub struct MmapCode {
+    mmap: MmapMut,
+    len: usize,
+}
+
+impl MmapCode {
+    pub fn idx(&self, idx: usize) -> BinaryField128b {
+        self.symbols()[idx]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn symbols(&self) -> &[BinaryField128b] {
+        //SAFETY: the map holds exactly len symbols written by new_mmap, is page aligned, and BinaryField128b is a plain wrapper around u128.
+        unsafe { slice::from_raw_parts(self.mmap.as_ptr() as *const BinaryField128b, self.len) }
+    }
+}
+
+impl Code<BinaryField128b> {
+    //Encodes message directly into a file at path, running the transform of each coset in place on the mapped region.
+    #[instrument(skip_all, name = "encode_mmap", level = "debug")]
+    pub fn new_mmap<F, N, Q>(message: &[F], ntt: &N, path: Q) -> Result<MmapCode, CodeError>
+    where
+        BinaryField128b: ExtensionField<F>,
+        F: BinaryField + TowerField,
+        N: AdditiveNTT<BinaryField128b>,
+        Q: AsRef<Path>,
+    {
+        let repacked_message = repack(message);
+        let len = repacked_message.len() * RATE;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((len * size_of::<BinaryField128b>()) as u64)?;
+
+        //SAFETY: the file was just created with the right size and is not shared with anything else.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        //SAFETY: as in MmapCode::symbols, the region holds len u128-sized, page aligned symbols.
+        let symbols =
+            unsafe { slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut BinaryField128b, len) };
+
+        for (i, coset) in symbols.chunks_mut(repacked_message.len()).enumerate() {
+            coset.copy_from_slice(&repacked_message);
+            ntt.forward_transform(coset, i as u32, 0)?;
+        }
+        mmap.flush()?;
+
+        Ok(MmapCode { mmap, len })
+    }
+}
+
+#[test]
+fn test_new_mmap() {
+    use binius_field::Field;
+    use binius_ntt::SingleThreadedNTT;
+    use rand::thread_rng;
+
+    use crate::utils::code::LOG_RATE;
+
+    let l = 8;
+    let poly: Vec<BinaryField128b> = (0..1 << l)
+        .map(|_| BinaryField128b::random(thread_rng()))
+        .collect();
+
+    let ntt = SingleThreadedNTT::<BinaryField128b>::new(l + LOG_RATE)
+        .unwrap()
+        .multithreaded();
+
+    let path = std::env::temp_dir().join("fri_binius_test_new_mmap.bin");
+    let mmap_code = Code::new_mmap(&poly, &ntt, &path).unwrap();
+    let code = Code::new(&poly, &ntt);
+
+    assert_eq!(mmap_code.len(), code.encoding.len());
+    for i in 0..code.encoding.len() {
+        assert_eq!(mmap_code.idx(i), code.idx(i));
+    }
+
+    drop(mmap_code);
+    std::fs::remove_file(path).unwrap();
+}
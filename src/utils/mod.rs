@@ -2,5 +2,19 @@ pub mod channel;
 pub mod code;
 pub mod merkle;
 pub mod mle;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 
-pub const TAU:usize = 7;
\ No newline at end of file
+use rayon::ThreadPoolBuilder;
+
+pub const TAU:usize = 7;
+
+//Runs f on a fresh rayon pool of num_threads threads, so every parallel iterator inside f is bounded by num_threads without touching RAYON_NUM_THREADS
+//or the global pool. Building the pool is not free, so benchmarks should time inside f rather than around with_threads.
+pub fn with_threads<R, F>(num_threads: usize, f: F) -> R where F: FnOnce() -> R + Send, R: Send {
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .expect("failed to build thread pool")
+        .install(f)
+}
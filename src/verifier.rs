@@ -1,17 +1,505 @@
+use std::{ error, fmt::{ self, Display, Formatter }, marker::PhantomData };
+
 use binius_field::{ BinaryField, BinaryField128b, ExtensionField, Field, TowerField };
-use binius_ntt::MultithreadedNTT;
+use binius_ntt::{ AdditiveNTT, MultithreadedNTT };
+use subtle::{ Choice, ConstantTimeEq };
 
 use crate::{
-    prover::{ EvalProof, FriCommitment },
+    prover::{ EvalProof, FriCommitment, FriProof, QueryPath },
     utils::{
         channel::{ self, Channel },
-        code::{ fold, LOG_RATE },
-        merkle::{ hash_field, hash_tuple, verify_merkle_path, VectorCommitment },
+        code::{ fold, fold_scheduled, Code, FoldSchedule, LOG_RATE },
+        merkle::{
+            hash_field,
+            hash_tuple,
+            tree_depth,
+            verify_merkle_path,
+            verify_merkle_proof,
+            Hash,
+            VectorCommitment,
+        },
         mle::{compute_row_batch, switch_view, LagrangeBases}, TAU,
     },
 };
 
+//Reasons a FriProof is rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    MalformedProof,
+    MerklePath { round: usize, query: usize },
+    FoldConsistency { round: usize, query: usize },
+    FinalLayer { query: usize },
+    QueryCount { expected: usize, received: usize },
+    BadProofLength { got: usize, expected: usize },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::MalformedProof => write!(f, "Proof does not have the expected shape"),
+            VerifyError::MerklePath { round, query } =>
+                write!(f, "Merkle path of query {query} does not open in round {round}"),
+            VerifyError::FoldConsistency { round, query } =>
+                write!(f, "Symbol not consistent at query {query} in round {round}"),
+            VerifyError::FinalLayer { query } =>
+                write!(f, "Query {query} does not match the final polynomial"),
+            VerifyError::QueryCount { expected, received } =>
+                write!(f, "Expected {expected} query openings, received {received}"),
+            VerifyError::BadProofLength { got, expected } =>
+                write!(f, "Merkle proof has {got} nodes, the committed tree has depth {expected}"),
+        }
+    }
+}
+
+impl error::Error for VerifyError {}
+
+impl FriProof {
+    //Replays the transcript of prove_fri and feeds every opening to a QueryVerifier.
+    pub fn verify<P, N>(
+        &self,
+        commitment: &VectorCommitment,
+        channel: &mut Channel,
+        ntt: &N
+    ) -> Result<(), VerifyError>
+        where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+    {
+        let mut query_verifier = QueryVerifier::from_transcript(
+            commitment,
+            &self.layer_roots,
+            &self.final_poly,
+            channel,
+            ntt
+        )?;
+        for opening in self.query_openings.iter() {
+            query_verifier.feed(opening)?;
+        }
+        query_verifier.finish()
+    }
+
+    //Checks that the proof has the shape params predict before anything is hashed: a root per fold round but the last, whose layer is sent as the final
+    //polynomial of 2^final_dim coefficients, and num_queries openings of a pair and a Merkle path per round, the path of round k opening a tree of depth
+    //message_dim + log_rate - k - 1. Parameters that fold past the message, or whose codeword length overflows usize, are malformed rather than a panic.
+    pub fn validate_shape(&self, params: &FriParams) -> Result<(), VerifyError> {
+        let log_len = match params.message_dim.checked_add(params.log_rate) {
+            Some(log_len) if log_len < usize::BITS as usize && params.final_dim <= params.message_dim => log_len,
+            _ => return Err(VerifyError::MalformedProof),
+        };
+
+        let rounds = params.num_rounds();
+        if self.layer_roots.len() + 1 != rounds || self.final_poly.len() != 1 << params.final_dim {
+            return Err(VerifyError::MalformedProof);
+        }
+        if self.query_openings.len() != params.num_queries {
+            return Err(VerifyError::QueryCount {
+                expected: params.num_queries,
+                received: self.query_openings.len(),
+            });
+        }
+
+        for opening in self.query_openings.iter() {
+            if opening.symbols.len() != rounds || opening.merkle_paths.len() != rounds {
+                return Err(VerifyError::MalformedProof);
+            }
+            for (round, merkle_path) in opening.merkle_paths.iter().enumerate() {
+                if merkle_path.len() != log_len - round - 1 {
+                    return Err(VerifyError::BadProofLength {
+                        got: merkle_path.len(),
+                        expected: log_len - round - 1,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//Checks FRI query openings one at a time as they arrive, so a bad opening is rejected before the rest are received and nothing is buffered.
+//The challenges, final polynomial and query positions come from replaying the transcript, as in FriProof::verify.
+pub struct QueryVerifier<'a, P, N> where N: AdditiveNTT<P>, P: BinaryField {
+    layer_commitments: Vec<VectorCommitment>,
+    challenges: Vec<BinaryField128b>,
+    final_layer: Code<BinaryField128b>,
+    queries: Vec<usize>,
+    received: usize,
+    ntt: &'a N,
+    _marker: PhantomData<P>,
+}
+
+impl<'a, P, N> QueryVerifier<'a, P, N>
+    where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+{
+    pub fn new(
+        commitment: &VectorCommitment,
+        layer_roots: &[Hash],
+        challenges: Vec<BinaryField128b>,
+        final_poly: &[BinaryField128b],
+        queries: Vec<usize>,
+        ntt: &'a N
+    ) -> Result<QueryVerifier<'a, P, N>, VerifyError> {
+        let log_len = commitment.depth + 1;
+        let rounds = layer_roots.len() + 1;
+        if
+            rounds + LOG_RATE > log_len ||
+            challenges.len() != rounds ||
+            final_poly.len() != 1 << (log_len - rounds - LOG_RATE)
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        //Layer round + 1 has 2^(log_len - round - 1) symbols, i.e. half as many leaves
+        let mut layer_commitments = vec![commitment.clone()];
+        layer_commitments.extend(
+            layer_roots
+                .iter()
+                .enumerate()
+                .map(|(round, root)| VectorCommitment {
+                    root: root.clone(),
+                    depth: log_len - round - 2,
+                })
+        );
+
+        let final_layer = Code::from_final_coeffs(final_poly, 1 << (log_len - rounds), rounds, ntt);
+
+        Ok(QueryVerifier {
+            layer_commitments,
+            challenges,
+            final_layer,
+            queries,
+            received: 0,
+            ntt,
+            _marker: PhantomData,
+        })
+    }
+
+    //Replays the transcript of prove_fri from the messages sent before the openings: the layer roots and the final polynomial.
+    pub fn from_transcript(
+        commitment: &VectorCommitment,
+        layer_roots: &[Hash],
+        final_poly: &[BinaryField128b],
+        channel: &mut Channel,
+        ntt: &'a N
+    ) -> Result<QueryVerifier<'a, P, N>, VerifyError> {
+        QueryVerifier::from_transcript_sampled(commitment, layer_roots, final_poly, channel, false, ntt)
+    }
+
+    //from_transcript for a prover that sampled its queries with Channel::sample_query_indices. dedupe must match the prover's, as it sets the number of
+    //openings expected.
+    pub fn from_transcript_sampled(
+        commitment: &VectorCommitment,
+        layer_roots: &[Hash],
+        final_poly: &[BinaryField128b],
+        channel: &mut Channel,
+        dedupe: bool,
+        ntt: &'a N
+    ) -> Result<QueryVerifier<'a, P, N>, VerifyError> {
+        let log_len = commitment.depth + 1;
+        let rounds = layer_roots.len() + 1;
+        if rounds + LOG_RATE > log_len {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        channel.observe_vector_commitment(commitment);
+
+        let mut challenges = Vec::with_capacity(rounds);
+        for round in 0..rounds {
+            challenges.push(channel.get_random_point().expect("failed to get folding challenge"));
+            if round < rounds - 1 {
+                channel.observe_vector_commitment(
+                    &(VectorCommitment {
+                        root: layer_roots[round].clone(),
+                        depth: log_len - round - 2,
+                    })
+                );
+            }
+        }
+
+        channel.observe_field_elems(final_poly).expect("failed to observe final polynomial");
+        let queries = channel.sample_query_indices(log_len, dedupe).expect("Failed to generate FRI queries.");
+
+        QueryVerifier::new(commitment, layer_roots, challenges, final_poly, queries, ntt)
+    }
+
+    //Verifies the opening of the next query: the Merkle path of each opened pair, that the pair contains the symbol folded from the previous layer,
+    //and that the last fold matches the re-encoded final polynomial.
+    pub fn feed(&mut self, opening: &QueryPath) -> Result<(), VerifyError> {
+        let query = self.received;
+        if query >= self.queries.len() {
+            return Err(VerifyError::QueryCount {
+                expected: self.queries.len(),
+                received: query + 1,
+            });
+        }
+
+        let rounds = self.challenges.len();
+        if opening.symbols.len() != rounds || opening.merkle_paths.len() != rounds {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        //Position of the current symbol in the current layer
+        let mut position = self.queries[query];
+        let mut folded_symbol = BinaryField128b::ZERO;
+        for round in 0..rounds {
+            let symbols = opening.symbols[round];
+            let leaf_index = position >> 1;
+
+            //Layer round holds 2^(log_len - round) symbols in pairs
+            let expected_depth = tree_depth(1 << (self.layer_commitments[0].depth + 1 - round), 2);
+            if
+                !verify_merkle_proof(
+                    &self.layer_commitments[round],
+                    hash_tuple(&symbols),
+                    leaf_index,
+                    &opening.merkle_paths[round],
+                    expected_depth
+                )?
+            {
+                return Err(VerifyError::MerklePath { round, query });
+            }
+
+            if round > 0 {
+                let expected_symbol = if position & 1 == 1 { symbols.1 } else { symbols.0 };
+                if folded_symbol != expected_symbol {
+                    return Err(VerifyError::FoldConsistency { round, query });
+                }
+            }
+
+            folded_symbol = fold(self.challenges[round], round, leaf_index, symbols.0, symbols.1, self.ntt);
+            position = leaf_index;
+        }
+
+        if folded_symbol != self.final_layer.idx(position) {
+            return Err(VerifyError::FinalLayer { query });
+        }
+
+        self.received += 1;
+        Ok(())
+    }
+
+    //Confirms that an opening was received for every query.
+    pub fn finish(self) -> Result<(), VerifyError> {
+        if self.received != self.queries.len() {
+            return Err(VerifyError::QueryCount {
+                expected: self.queries.len(),
+                received: self.received,
+            });
+        }
+        Ok(())
+    }
+}
+
+//Public parameters of a FRI proof: a message of 2^message_dim coefficients encoded at rate 2^-log_rate, queried num_queries times and folded until
+//2^final_dim coefficients remain, sent as the final polynomial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FriParams {
+    pub message_dim: usize,
+    pub log_rate: usize,
+    pub num_queries: usize,
+    pub final_dim: usize,
+}
+
+impl FriParams {
+    //Number of folds from 2^message_dim coefficients down to 2^final_dim, one challenge each.
+    pub fn num_rounds(&self) -> usize {
+        self.message_dim - self.final_dim
+    }
+}
+
+//Verifier of prove_fri proofs built from the FriParams alone. The shape of the proof and the depth of the commitment are derived from the parameters rather
+//than read from the proof, and every challenge is drawn from the channel as in FriProof::verify, so a proof only carries roots, openings and the final
+//polynomial.
+pub struct FriVerifier<'a, P, N> where N: AdditiveNTT<P>, P: BinaryField {
+    params: FriParams,
+    ntt: &'a N,
+    _marker: PhantomData<P>,
+}
+
+impl<'a, P, N> FriVerifier<'a, P, N>
+    where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+{
+    pub fn new(params: FriParams, ntt: &'a N) -> FriVerifier<'a, P, N> {
+        assert_eq!(params.log_rate, LOG_RATE, "The rate is fixed at 2^-{LOG_RATE}");
+        assert!(
+            params.final_dim < params.message_dim,
+            "Cannot fold from dimension {} to {}",
+            params.message_dim,
+            params.final_dim
+        );
+        assert!(
+            ntt.log_domain_size() >= params.message_dim + params.log_rate,
+            "NTT domain too small for a codeword of length 2^{}",
+            params.message_dim + params.log_rate
+        );
+
+        FriVerifier { params, ntt, _marker: PhantomData }
+    }
+
+    pub fn verify(&self, commitment_root: &Hash, proof: &FriProof, channel: &mut Channel) -> Result<(), VerifyError> {
+        proof.validate_shape(&self.params)?;
+
+        let commitment = VectorCommitment {
+            root: commitment_root.clone(),
+            depth: self.params.message_dim + self.params.log_rate - 1,
+        };
+        let mut query_verifier = QueryVerifier::from_transcript(
+            &commitment,
+            &proof.layer_roots,
+            &proof.final_poly,
+            channel,
+            self.ntt
+        )?;
+        for opening in proof.query_openings.iter() {
+            query_verifier.feed(opening)?;
+        }
+        query_verifier.finish()
+    }
+}
+
+//Checks the fold of the opened pair (left, right) around idx against both verifier obligations of an eq-based round: the folded symbol must equal
+//expected, the symbol the next layer opens at idx >> 1, and it must equal the eq polynomial's value there. eq_point is the eq point over the variables
+//of the layer at round, whose codeword the pair is opened from, so the eq value is eq(eq_point[0], r) times symbol idx >> 1 of the codeword of
+//eq(eq_point[1..], .) on the domain of round + 1. It is computed here from the public point rather than taken from the prover.
+pub fn verify_fold_with_eq<P, N>(
+    round: usize,
+    idx: usize,
+    left: BinaryField128b,
+    right: BinaryField128b,
+    r: BinaryField128b,
+    expected: BinaryField128b,
+    eq_point: &[BinaryField128b],
+    ntt: &N
+) -> bool
+    where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+{
+    assert!(!eq_point.is_empty(), "No variable left to fold");
+
+    let folded = fold(r, round, idx >> 1, left, right, ntt);
+    let eq_value =
+        (BinaryField128b::ONE + eq_point[0] + r) * eq_layer_symbol(&eq_point[1..], round + 1, idx >> 1, ntt);
+    folded == expected && folded == eq_value
+}
+
+//Symbol idx of the layer at round whose message is the eq table of eq_point, evaluated in O(2^eq_point.len()) without encoding the layer. Same sweep as
+//decoding one position: every variable i halves the table with the twiddle of round + i at idx >> (i + 1).
+fn eq_layer_symbol<P, N>(eq_point: &[BinaryField128b], round: usize, idx: usize, ntt: &N) -> BinaryField128b
+    where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+{
+    if eq_point.is_empty() {
+        return BinaryField128b::ONE;
+    }
+
+    let mut vals = compute_eq_table(eq_point);
+    for i in 0..eq_point.len() {
+        let mut s_i = BinaryField128b::from(ntt.get_subspace_eval(round + i, idx >> (i + 1)));
+        if (idx >> i) & 1 == 1 {
+            s_i += BinaryField128b::ONE;
+        }
+        let half_len = vals.len() >> 1;
+        for k in 0..half_len {
+            vals[k] = vals[k << 1] + s_i * vals[(k << 1) | 1];
+        }
+        vals.truncate(half_len);
+    }
+    vals[0]
+}
+
+//Checks one fold of a query: the opened pair (left, right) around idx folds under r to expected, with the twiddle read from the same schedule the prover
+//folded with.
+pub fn verify_fold_step(
+    schedule: &FoldSchedule,
+    round: usize,
+    idx: usize,
+    left: BinaryField128b,
+    right: BinaryField128b,
+    r: BinaryField128b,
+    expected: BinaryField128b
+) -> bool {
+    fold_scheduled(r, round, idx >> 1, left, right, schedule) == expected
+}
 
+//verify_fold_step with the comparison done in constant time. Against a verifier whose timing is observable, e.g. one checking queries for a remote
+//party, a branch on each comparison shows which query failed and how far verification got. Challenges and query positions are public in the transcript,
+//so the fold's branch on r and its twiddle lookup by idx leak nothing new, only the outcome is hidden. Callers should combine the Choices of all steps
+//with & and branch once at the end.
+pub fn verify_fold_step_ct(
+    schedule: &FoldSchedule,
+    round: usize,
+    idx: usize,
+    left: BinaryField128b,
+    right: BinaryField128b,
+    r: BinaryField128b,
+    expected: BinaryField128b
+) -> Choice {
+    fold_scheduled(r, round, idx >> 1, left, right, schedule).val().ct_eq(&expected.val())
+}
+
+//Checks an arity fold of a query: siblings are the arity symbols at parent_idx * arity.. of the layer reached after round folds, and folding them through
+//log2(arity) rounds with challenges, as fold_rounds(challenges, round, ntt) does over the whole layer, must give parent_value. Each sub-round halves the
+//siblings with the twiddles of the pairs' absolute positions, arity - 1 folds in total.
+pub fn verify_fold_step_arity<P, N>(
+    round: usize,
+    parent_idx: usize,
+    siblings: &[BinaryField128b],
+    challenges: &[BinaryField128b],
+    parent_value: BinaryField128b,
+    arity: usize,
+    ntt: &N
+) -> bool
+    where BinaryField128b: ExtensionField<P>, P: BinaryField, N: AdditiveNTT<P>
+{
+    if !arity.is_power_of_two() || arity < 2 || siblings.len() != arity || challenges.len() != arity.trailing_zeros() as usize {
+        return false;
+    }
+
+    let mut vals = siblings.to_vec();
+    for (k, r) in challenges.iter().enumerate() {
+        let base_idx = (parent_idx * arity) >> (k + 1);
+        vals = vals
+            .chunks_exact(2)
+            .enumerate()
+            .map(|(j, pair)| fold(*r, round + k, base_idx + j, pair[0], pair[1], ntt))
+            .collect();
+    }
+    vals[0] == parent_value
+}
+
+//Degree bound of the terminal layer: the final polynomial sent when FRI stops early must have no nonzero coefficient at or past 1 << expected_log_degree,
+//otherwise the prover stopped folding with more degrees of freedom than the layer allows.
+pub fn verify_final_degree(final_coeffs: &[BinaryField128b], expected_log_degree: usize) -> bool {
+    final_coeffs.iter().skip(1 << expected_log_degree).all(|coeff| *coeff == BinaryField128b::ZERO)
+}
+
+//Glue between sumcheck and FRI. The sumcheck over h(x) = t(x) * eq(z, x) ends in the claim sumcheck_claim = h(r) = t(r) * eq(z, r) at its challenges r.
+//t(r) is not known to the verifier, but folding the codeword of t with the same challenges in the same order yields exactly t(r), since fold_all at r is
+//the multilinear evaluation of the message at r. The verifier computes eq(z, r) = prod_i (1 + z_i + r_i) itself and passes it as eq_correction, and the
+//two protocols agree iff sumcheck_claim = fri_final * eq_correction. Any other weight the sumcheck carried, e.g. a tensor batching factor, goes into
+//eq_correction too.
+pub fn verify_fri_sumcheck_link(
+    fri_final: BinaryField128b,
+    sumcheck_claim: BinaryField128b,
+    eq_correction: BinaryField128b
+) -> bool {
+    fri_final * eq_correction == sumcheck_claim
+}
+
+//Batching consistency of one query: openings[j] is (position, value) of the j-th batched codeword, and expected_combined the opening of the combined
+//codeword sum_j coeffs[j] * code_j at the same position. Openings from different positions, or a count that does not match coeffs, are rejected rather
+//than combined.
+pub fn verify_batch_combination(
+    openings: &[(usize, BinaryField128b)],
+    coeffs: &[BinaryField128b],
+    expected_combined: BinaryField128b
+) -> bool {
+    if openings.is_empty() || openings.len() != coeffs.len() || openings.iter().any(|(idx, _)| *idx != openings[0].0) {
+        return false;
+    }
+
+    let combined: BinaryField128b = openings
+        .iter()
+        .zip(coeffs.iter())
+        .map(|((_, val), coeff)| *coeff * *val)
+        .sum();
+    combined == expected_combined
+}
 
 pub fn verify<P>(
     commitment: &FriCommitment,